@@ -0,0 +1,57 @@
+use std::mem;
+
+/// The size of an element in bytes, used for byte budgets, see
+/// [QueueIter::with_byte_limit](crate::QueueIter::with_byte_limit). Implementations should return
+/// the size of the payload, e.g. the heap allocated data, which typically dominates the memory
+/// usage of elements varying in size.
+///
+/// # Examples
+///
+/// ```
+/// use enqueue::ByteSize;
+///
+/// assert_eq!(vec![0u32; 4].byte_size(), 16);
+/// assert_eq!(String::from("abc").byte_size(), 3);
+/// ```
+pub trait ByteSize {
+    /// The size of this element in bytes.
+    fn byte_size(&self) -> usize;
+}
+
+/// The length times the size of `T`; spare capacity is not accounted for.
+impl<T> ByteSize for Vec<T> {
+    fn byte_size(&self) -> usize {
+        self.len() * mem::size_of::<T>()
+    }
+}
+
+/// The length in bytes; spare capacity is not accounted for.
+impl ByteSize for String {
+    fn byte_size(&self) -> usize {
+        self.len()
+    }
+}
+
+/// The length times the size of `T`.
+impl<T> ByteSize for Box<[T]> {
+    fn byte_size(&self) -> usize {
+        self.len() * mem::size_of::<T>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test() {
+        let bytes = Vec::<u8>::with_capacity(42);
+        assert_eq!(bytes.byte_size(), 0);
+
+        let bytes = vec![0u8; 42].into_boxed_slice();
+        assert_eq!(bytes.byte_size(), 42);
+
+        assert_eq!(vec![0u64; 3].byte_size(), 24);
+        assert_eq!(String::from("äb").byte_size(), 3);
+    }
+}
@@ -0,0 +1,153 @@
+use crate::{QueueStream, StreamEnqueuer};
+use futures_core::Stream;
+use futures_util::{stream::FuturesUnordered, StreamExt};
+use std::{future::Future, task::Poll};
+
+/// Process the elements of the given [QueueStream] with the given async function, keeping up to
+/// `limit` invocations in flight concurrently. The function is also given a [StreamEnqueuer] such
+/// that it can enqueue newly discovered elements. Completes once the queue is idle, i.e. there are
+/// no initial and no enqueued elements, and no invocation is in flight.
+///
+/// Notice that elements enqueued by other means, e.g. by further [StreamEnqueuer] handles, are only
+/// processed if they are enqueued before completion.
+///
+/// If the queue has a capacity limit, see [QueueStream::with_capacity_limit], no new invocations
+/// are started while `limit` invocations are in flight. Hence, if all of them wait for room via
+/// [StreamEnqueuer::enqueue], this deadlocks: either make sure the capacity suffices for all
+/// elements the invocations in flight may enqueue or use [StreamEnqueuer::try_enqueue].
+///
+/// # Panics
+///
+/// Panics if `limit` is zero.
+///
+/// # Examples
+///
+/// ```
+/// use enqueue::{queue_stream, run_concurrent};
+/// use futures::{executor::block_on, stream};
+/// use std::sync::Mutex;
+///
+/// let numbers = queue_stream(stream::iter([1]));
+/// let visited = Mutex::new(vec![]);
+///
+/// block_on(run_concurrent(numbers, 4, |n, enqueuer| {
+///     let visited = &visited;
+///     async move {
+///         visited.lock().unwrap().push(n);
+///         if n < 4 {
+///             enqueuer.enqueue(2 * n).await.unwrap();
+///             enqueuer.enqueue(2 * n + 1).await.unwrap();
+///         }
+///     }
+/// }));
+///
+/// let mut visited = visited.into_inner().unwrap();
+/// visited.sort();
+/// assert_eq!(visited, [1, 2, 3, 4, 5, 6, 7]);
+/// ```
+pub async fn run_concurrent<S, F, Fut>(mut queue: QueueStream<S>, limit: usize, mut f: F)
+where
+    S: Stream,
+    F: FnMut(S::Item, StreamEnqueuer<S::Item>) -> Fut,
+    Fut: Future<Output = ()>,
+{
+    assert!(limit > 0, "limit must not be zero");
+
+    let mut in_flight = FuturesUnordered::new();
+    std::future::poll_fn(|cx| loop {
+        let mut idle = false;
+        while in_flight.len() < limit {
+            match queue.poll_next_item(cx, true) {
+                Poll::Ready(Some(item)) => in_flight.push(f(item, queue.enqueuer())),
+                Poll::Ready(None) => {
+                    idle = true;
+                    break;
+                }
+                Poll::Pending => break,
+            }
+        }
+
+        match in_flight.poll_next_unpin(cx) {
+            Poll::Ready(Some(())) => continue,
+            Poll::Ready(None) if idle => return Poll::Ready(()),
+            Poll::Ready(None) | Poll::Pending => return Poll::Pending,
+        }
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::queue_stream;
+    use futures::{executor::block_on, stream};
+    use std::{
+        collections::{HashMap, HashSet},
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Mutex,
+        },
+        task::Context,
+    };
+
+    /// Yield once to the executor.
+    async fn yield_now() {
+        let mut yielded = false;
+        std::future::poll_fn(|cx: &mut Context<'_>| {
+            if yielded {
+                Poll::Ready(())
+            } else {
+                yielded = true;
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        })
+        .await
+    }
+
+    #[test]
+    fn test() {
+        // Mock service with pages linking to other pages, including cycles.
+        let service = HashMap::from([
+            ("/", vec!["/a", "/b", "/c"]),
+            ("/a", vec!["/", "/a/1", "/a/2"]),
+            ("/b", vec!["/b/1"]),
+            ("/c", vec!["/a/1"]),
+            ("/a/1", vec![]),
+            ("/a/2", vec!["/b/1"]),
+            ("/b/1", vec!["/"]),
+        ]);
+        let fetch = |page| {
+            let service = &service;
+            async move {
+                yield_now().await;
+                service[page].clone()
+            }
+        };
+
+        let pages = queue_stream(stream::iter(["/"]));
+        let crawled = Mutex::new(HashSet::from(["/"]));
+        let in_flight = AtomicUsize::new(0);
+        let max_in_flight = AtomicUsize::new(0);
+
+        let crawl = run_concurrent(pages, 2, |page, enqueuer| {
+            let (crawled, in_flight, max_in_flight) = (&crawled, &in_flight, &max_in_flight);
+            async move {
+                let n = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                max_in_flight.fetch_max(n, Ordering::SeqCst);
+
+                for link in fetch(page).await {
+                    if crawled.lock().unwrap().insert(link) {
+                        enqueuer.enqueue(link).await.unwrap();
+                    }
+                }
+
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+            }
+        });
+        block_on(crawl);
+
+        assert_eq!(crawled.into_inner().unwrap().len(), service.len());
+        assert_eq!(max_in_flight.into_inner(), 2);
+    }
+}
@@ -0,0 +1,185 @@
+use crate::{queue_iter, QueueIter, Source};
+use std::{
+    collections::{hash_map::RandomState, HashSet},
+    hash::{BuildHasher, Hash},
+};
+
+/// Create an `Iterator` allowing for enqueuing elements to its end, silently dropping elements
+/// which have already been seen, i.e. yielded from the initial `Iterator` or enqueued.
+///
+/// # Examples
+///
+/// ```
+/// use enqueue::dedup_queue_iter;
+///
+/// let i = [1, 2, 1];
+/// let mut i = dedup_queue_iter(i);
+///
+/// i.enqueue(2);
+/// i.enqueue(3);
+/// assert!(!i.enqueue_if_new(3));
+/// assert_eq!(i.next(), Some(1));
+/// assert_eq!(i.next(), Some(2));
+/// assert_eq!(i.next(), Some(3));
+/// assert_eq!(i.next(), None);
+/// ```
+pub fn dedup_queue_iter<I>(initial: I) -> DedupQueueIter<I::IntoIter, I::Item, Identity<I::Item>>
+where
+    I: IntoIterator,
+    I::Item: Clone + Hash + Eq,
+{
+    dedup_queue_iter_by_key(initial, Clone::clone)
+}
+
+/// Create an `Iterator` allowing for enqueuing elements to its end, silently dropping elements
+/// with keys, extracted by the given function, which have already been seen, i.e. yielded from the
+/// initial `Iterator` or enqueued.
+///
+/// # Examples
+///
+/// ```
+/// use enqueue::dedup_queue_iter_by_key;
+///
+/// let i = std::iter::once("a");
+/// let mut i = dedup_queue_iter_by_key(i, |s: &&str| s.len());
+///
+/// assert_eq!(i.next(), Some("a"));
+/// i.enqueue("b");
+/// i.enqueue("cd");
+/// assert_eq!(i.next(), Some("cd"));
+/// assert_eq!(i.next(), None);
+/// ```
+pub fn dedup_queue_iter_by_key<I, K, F>(initial: I, key: F) -> DedupQueueIter<I::IntoIter, K, F>
+where
+    I: IntoIterator,
+    K: Hash + Eq,
+    F: FnMut(&I::Item) -> K,
+{
+    DedupQueueIter {
+        queue: queue_iter(initial),
+        seen: HashSet::default(),
+        key,
+    }
+}
+
+/// Key extraction function of a [DedupQueueIter] using the elements themselves as keys.
+pub type Identity<T> = fn(&T) -> T;
+
+/// An `Iterator` allowing for enqueuing elements to its end, silently dropping elements with keys
+/// which have already been seen, i.e. yielded from the initial `Iterator` or enqueued, e.g. for
+/// graph traversals. Enqueued elements are checked when enqueued, initial ones when they are
+/// yielded. Built on [QueueIter].
+pub struct DedupQueueIter<I, K, F, S = RandomState>
+where
+    I: Iterator,
+{
+    queue: QueueIter<I>,
+    seen: HashSet<K, S>,
+    key: F,
+}
+
+impl<I, K, F, S> DedupQueueIter<I, K, F, S>
+where
+    I: Iterator,
+    K: Hash + Eq,
+    F: FnMut(&I::Item) -> K,
+    S: BuildHasher,
+{
+    /// Use the given `BuildHasher` for the set of seen keys.
+    pub fn with_hasher<T>(self, hasher: T) -> DedupQueueIter<I, K, F, T>
+    where
+        T: BuildHasher,
+    {
+        let mut seen = HashSet::with_hasher(hasher);
+        seen.extend(self.seen);
+        DedupQueueIter {
+            queue: self.queue,
+            seen,
+            key: self.key,
+        }
+    }
+
+    /// Enqueue an element to the end of this `Iterator`, unless its key has already been seen.
+    pub fn enqueue(&mut self, item: I::Item) {
+        self.enqueue_if_new(item);
+    }
+
+    /// Enqueue an element to the end of this `Iterator`, unless its key has already been seen,
+    /// and return whether it has been enqueued.
+    pub fn enqueue_if_new(&mut self, item: I::Item) -> bool {
+        let new = self.seen.insert((self.key)(&item));
+        if new {
+            self.queue.enqueue(item);
+        }
+        new
+    }
+
+    /// The set of seen keys, i.e. of the elements yielded from the initial `Iterator` or enqueued.
+    pub fn visited(&self) -> &HashSet<K, S> {
+        &self.seen
+    }
+
+    /// Consume this `Iterator` and return the set of seen keys, see [DedupQueueIter::visited].
+    pub fn into_visited(self) -> HashSet<K, S> {
+        self.seen
+    }
+}
+
+impl<I, K, F, S> Iterator for DedupQueueIter<I, K, F, S>
+where
+    I: Iterator,
+    K: Hash + Eq,
+    F: FnMut(&I::Item) -> K,
+    S: BuildHasher,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.queue.next_with_source()? {
+                // Enqueued elements have already been checked.
+                (Source::Enqueued, item) => return Some(item),
+
+                (Source::Initial, item) => {
+                    if self.seen.insert((self.key)(&item)) {
+                        return Some(item);
+                    }
+                }
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::{BTreeSet, HashMap};
+
+    #[test]
+    fn test() {
+        // Cyclic graph.
+        let graph = HashMap::from([
+            (1, vec![2, 3]),
+            (2, vec![3, 1]),
+            (3, vec![1, 4]),
+            (4, vec![]),
+        ]);
+        let mut nodes = dedup_queue_iter([1]);
+        let mut visited = vec![];
+
+        while let Some(n) = nodes.next() {
+            visited.push(n);
+            for &m in &graph[&n] {
+                nodes.enqueue(m);
+            }
+        }
+
+        assert_eq!(visited, [1, 2, 3, 4]);
+        let visited = nodes.into_visited().into_iter().collect::<BTreeSet<_>>();
+        assert_eq!(visited, BTreeSet::from([1, 2, 3, 4]));
+    }
+}
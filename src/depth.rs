@@ -0,0 +1,182 @@
+use crate::{queue_iter, QueueIter};
+
+/// Create an `Iterator` allowing for enqueuing elements to its end, tracking the depth of each
+/// element: initial elements have depth zero and elements enqueued while processing an element of
+/// depth `n` have depth `n + 1`.
+///
+/// # Examples
+///
+/// ```
+/// use enqueue::depth_queue_iter;
+///
+/// let i = std::iter::once(666);
+/// let mut i = depth_queue_iter(i).with_max_depth(1);
+///
+/// assert_eq!(i.next_with_depth(), Some((0, 666)));
+/// assert!(i.enqueue(42));
+/// assert_eq!(i.next_with_depth(), Some((1, 42)));
+/// assert_eq!(i.current_depth(), 1);
+/// assert!(!i.enqueue(7));
+/// assert_eq!(i.next(), None);
+/// ```
+pub fn depth_queue_iter<I>(initial: I) -> DepthQueueIter<I::IntoIter>
+where
+    I: IntoIterator,
+{
+    DepthQueueIter {
+        queue: queue_iter(Initial(initial.into_iter())),
+        current_depth: 0,
+        max_depth: None,
+    }
+}
+
+/// An `Iterator` allowing for enqueuing elements to its end, tracking the depth of each element
+/// relative to the element being processed, e.g. for breadth-first crawlers or iterative-deepening
+/// searches. Built on [QueueIter].
+pub struct DepthQueueIter<I>
+where
+    I: Iterator,
+{
+    queue: QueueIter<Initial<I>>,
+    current_depth: usize,
+    max_depth: Option<usize>,
+}
+
+impl<I> DepthQueueIter<I>
+where
+    I: Iterator,
+{
+    /// Reject enqueuing elements which would have a depth greater than the given one.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// The depth of the element yielded last, zero if none has been yielded yet.
+    pub fn current_depth(&self) -> usize {
+        self.current_depth
+    }
+
+    /// Enqueue an element to the end of this `Iterator` with a depth one greater than the current
+    /// one, unless that exceeds the maximum depth, and return whether it has been enqueued.
+    pub fn enqueue(&mut self, item: I::Item) -> bool {
+        let depth = self.current_depth + 1;
+        let accepted = self.max_depth.map_or(true, |max_depth| depth <= max_depth);
+        if accepted {
+            self.queue.enqueue((depth, item));
+        }
+        accepted
+    }
+
+    /// Return all elements of the next level, i.e. with the depth of the element to be yielded
+    /// next, as a batch. Elements enqueued while processing the batch have a depth one greater than
+    /// the one of the batch, i.e. belong to the following level.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use enqueue::IteratorExt;
+    ///
+    /// let mut i = [1, 2].depth_queue_iter();
+    ///
+    /// assert_eq!(i.next_level(), Some(vec![1, 2]));
+    /// i.enqueue(3);
+    /// i.enqueue(4);
+    /// assert_eq!(i.next_level(), Some(vec![3, 4]));
+    /// assert_eq!(i.current_depth(), 1);
+    /// assert_eq!(i.next_level(), None);
+    /// ```
+    pub fn next_level(&mut self) -> Option<Vec<I::Item>> {
+        let (depth, item) = self.next_with_depth()?;
+        let mut level = vec![item];
+        while matches!(self.queue.peek(), Some((next_depth, _)) if *next_depth == depth) {
+            let (_, item) = self.queue.next().expect("peeked element");
+            level.push(item);
+        }
+        Some(level)
+    }
+
+    /// Like `next`, but also returning the depth of the element.
+    pub fn next_with_depth(&mut self) -> Option<(usize, I::Item)> {
+        let (depth, item) = self.queue.next()?;
+        self.current_depth = depth;
+        Some((depth, item))
+    }
+}
+
+impl<I> Iterator for DepthQueueIter<I>
+where
+    I: Iterator,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_with_depth().map(|(_, item)| item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.queue.size_hint()
+    }
+}
+
+/// Initial elements with depth zero.
+struct Initial<I>(I);
+
+impl<I> Iterator for Initial<I>
+where
+    I: Iterator,
+{
+    type Item = (usize, I::Item);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|item| (0, item))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test() {
+        // Crawl "pages" 0..: page n links to pages 2n + 1 and 2n + 2.
+        let mut pages = depth_queue_iter([0]).with_max_depth(2);
+        let mut crawled = vec![];
+
+        while let Some((depth, n)) = pages.next_with_depth() {
+            crawled.push((depth, n));
+            let accepted = [2 * n + 1, 2 * n + 2].map(|m| pages.enqueue(m));
+            assert_eq!(accepted, [depth < 2; 2]);
+        }
+
+        assert_eq!(
+            crawled,
+            [(0, 0), (1, 1), (1, 2), (2, 3), (2, 4), (2, 5), (2, 6)]
+        );
+    }
+
+    #[test]
+    fn test_next_level() {
+        // Per-level sums of a binary tree with nodes 1.., stopping at the first sum above 100.
+        let mut nodes = depth_queue_iter([1]);
+        let mut sums = vec![];
+
+        while let Some(level) = nodes.next_level() {
+            let sum = level.iter().sum::<u32>();
+            sums.push((nodes.current_depth(), sum));
+            if sum > 100 {
+                break;
+            }
+            for n in level {
+                nodes.enqueue(2 * n);
+                nodes.enqueue(2 * n + 1);
+            }
+        }
+
+        assert_eq!(sums, [(0, 1), (1, 5), (2, 22), (3, 92), (4, 376)]);
+    }
+}
@@ -0,0 +1,587 @@
+use crate::{Frontier, InspectFrontier};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    collections::{vec_deque, VecDeque},
+    fmt::{self, Debug, Formatter},
+    fs::{self, File, OpenOptions},
+    io::{self, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+};
+
+const ENQUEUE: u8 = 0;
+const CONSUME: u8 = 1;
+const INSERT: u8 = 2;
+
+/// Length of the position prefixing the payload of consume and insert records.
+const POSITION_LEN: usize = 8;
+
+/// Kind (1 byte), payload length (4 bytes) and checksum (4 bytes).
+const HEADER_LEN: usize = 9;
+
+const DEFAULT_COMPACT_AFTER: usize = 1024;
+
+/// [Frontier] yielding elements in FIFO order like [Fifo](crate::Fifo), but persisting them in a
+/// write-ahead log file such that processing can survive restarts: every pushed and every popped
+/// element is appended to the log, from which [Durable::open] reconstructs the pending elements.
+/// The log is compacted periodically, see [Durable::with_compact_after].
+///
+/// Each record of the log is protected by a checksum. An incomplete or corrupt final record, e.g.
+/// due to a crash while writing, is discarded when opening the log; a corrupt record followed by
+/// further ones is reported as an error.
+///
+/// Elements buffered at the front of a [QueueIter](crate::QueueIter), i.e. peeked or put back, are
+/// logged as well, see [Frontier::front_inserted], hence an element is only considered consumed
+/// once yielded or removed, i.e. elements are processed at most once. Notice that mutating a
+/// buffered element, see [QueueIter::peek_mut](crate::QueueIter::peek_mut), is not logged. Records
+/// are written to the operating system immediately, but only guaranteed to be on disk after
+/// [Durable::sync].
+///
+/// # Panics
+///
+/// Pushing and popping elements panics if writing to the log fails; use [Durable::try_push] and
+/// [Durable::try_pop] to handle such errors.
+///
+/// # Examples
+///
+/// ```
+/// use enqueue::{Durable, IteratorExt};
+///
+/// let dir = tempfile::tempdir().unwrap();
+/// let path = dir.path().join("queue.log");
+///
+/// let durable = Durable::open(&path).unwrap();
+/// let mut i = std::iter::empty().queue_iter_with(durable);
+/// i.enqueue(1);
+/// i.enqueue(2);
+/// assert_eq!(i.next(), Some(1));
+/// drop(i);
+///
+/// let durable = Durable::open(&path).unwrap();
+/// let mut i = std::iter::empty().queue_iter_with(durable);
+/// assert_eq!(i.next(), Some(2));
+/// assert_eq!(i.next(), None);
+/// ```
+pub struct Durable<T> {
+    /// The serialized elements buffered at the front of a [QueueIter](crate::QueueIter), which
+    /// precede the queue in the log.
+    front: VecDeque<Vec<u8>>,
+    queue: VecDeque<T>,
+    path: PathBuf,
+    file: File,
+    consumed: usize,
+    compact_after: usize,
+}
+
+impl<T> Durable<T>
+where
+    T: Serialize + DeserializeOwned,
+{
+    /// Open the log at the given path, creating it if it does not exist, and reconstruct the
+    /// pending elements. An incomplete or corrupt final record is discarded and truncated.
+    pub fn open<P>(path: P) -> io::Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref().to_path_buf();
+        let mut file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(&path)?;
+        let mut bytes = vec![];
+        file.read_to_end(&mut bytes)?;
+
+        let mut queue = VecDeque::new();
+        let mut consumed = 0;
+        let mut offset = 0;
+        while offset < bytes.len() {
+            let Some((kind, payload)) = decode(&bytes[offset..]) else {
+                if is_torn(&bytes[offset..]) {
+                    file.set_len(offset as u64)?;
+                    break;
+                }
+                return Err(invalid_data(format!("corrupt record at offset {offset}")));
+            };
+
+            match kind {
+                ENQUEUE => {
+                    let item = bincode::deserialize(payload).map_err(invalid_data)?;
+                    queue.push_back(item);
+                }
+
+                INSERT => {
+                    let Some((n, payload)) =
+                        split_position(payload).filter(|(n, _)| *n <= queue.len())
+                    else {
+                        return Err(invalid_data(format!(
+                            "insert record with invalid position at offset {offset}"
+                        )));
+                    };
+                    let item = bincode::deserialize(payload).map_err(invalid_data)?;
+                    queue.insert(n, item);
+                }
+
+                CONSUME => {
+                    let n = split_position(payload).filter(|(_, rest)| rest.is_empty());
+                    if n.and_then(|(n, _)| queue.remove(n)).is_none() {
+                        return Err(invalid_data(format!(
+                            "consume record without pending element at offset {offset}"
+                        )));
+                    }
+                    consumed += 1;
+                }
+
+                kind => {
+                    return Err(invalid_data(format!(
+                        "unknown record kind {kind} at offset {offset}"
+                    )))
+                }
+            }
+
+            offset += HEADER_LEN + payload.len();
+        }
+
+        Ok(Self {
+            front: VecDeque::new(),
+            queue,
+            path,
+            file,
+            consumed,
+            compact_after: DEFAULT_COMPACT_AFTER,
+        })
+    }
+
+    /// Compact the log once the given number of elements have been consumed since the last
+    /// compaction and at least as many as there are pending elements, such that compaction costs
+    /// amortized constant time. Defaults to 1024.
+    pub fn with_compact_after(mut self, compact_after: usize) -> Self {
+        self.compact_after = compact_after;
+        self
+    }
+
+    /// Add an element, appending it to the log.
+    pub fn try_push(&mut self, item: T) -> io::Result<()> {
+        let payload = bincode::serialize(&item).map_err(invalid_data)?;
+        self.file.write_all(&encode(ENQUEUE, &payload)?)?;
+        self.queue.push_back(item);
+        Ok(())
+    }
+
+    /// Remove the element to be yielded next, if any, appending its consumption to the log.
+    pub fn try_pop(&mut self) -> io::Result<Option<T>> {
+        if self.queue.is_empty() {
+            return Ok(None);
+        }
+        self.consume(self.front.len())?;
+        Ok(self.queue.pop_front())
+    }
+
+    /// Append the consumption of the element at the given position of the front followed by the
+    /// queue to the log.
+    fn consume(&mut self, n: usize) -> io::Result<()> {
+        // Compact before consuming, such that an error does not lose the element.
+        let len = self.front.len() + self.queue.len();
+        if self.consumed >= self.compact_after && self.consumed >= len {
+            self.compact()?;
+        }
+
+        self.file.write_all(&encode(CONSUME, &position(n))?)?;
+        self.consumed += 1;
+        Ok(())
+    }
+
+    /// Append an element buffered at the given position of the front to the log, see
+    /// [Frontier::front_inserted].
+    fn insert_front(&mut self, n: usize, item: &T) -> io::Result<()> {
+        let payload = bincode::serialize(item).map_err(invalid_data)?;
+        let mut record = position(n);
+        record.extend(&payload);
+        self.file.write_all(&encode(INSERT, &record)?)?;
+        self.front.insert(n, payload);
+        Ok(())
+    }
+
+    /// Append the removal of the element at the given position of the front to the log, see
+    /// [Frontier::front_removed].
+    fn remove_front(&mut self, n: usize) -> io::Result<()> {
+        self.consume(n)?;
+        self.front.remove(n);
+        Ok(())
+    }
+
+    /// Compact the log, i.e. rewrite it to only contain the pending elements, including the ones
+    /// buffered at the front of a [QueueIter](crate::QueueIter). The new log is written to a
+    /// temporary file next to the log which then atomically replaces it.
+    pub fn compact(&mut self) -> io::Result<()> {
+        let mut compact_path = self.path.clone().into_os_string();
+        compact_path.push(".compact");
+        let compact_path = PathBuf::from(compact_path);
+
+        let mut file = BufWriter::new(File::create(&compact_path)?);
+        for payload in &self.front {
+            file.write_all(&encode(ENQUEUE, payload)?)?;
+        }
+        for item in &self.queue {
+            let payload = bincode::serialize(item).map_err(invalid_data)?;
+            file.write_all(&encode(ENQUEUE, &payload)?)?;
+        }
+        file.into_inner()
+            .map_err(|error| error.into_error())?
+            .sync_all()?;
+
+        fs::rename(&compact_path, &self.path)?;
+        sync_dir(&self.path)?;
+        self.file = OpenOptions::new().append(true).open(&self.path)?;
+        self.consumed = 0;
+        Ok(())
+    }
+}
+
+impl<T> Durable<T> {
+    /// Flush the log to disk.
+    pub fn sync(&self) -> io::Result<()> {
+        self.file.sync_data()
+    }
+
+    /// The path of the log.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl<T> Debug for Durable<T>
+where
+    T: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Durable")
+            .field("queue", &self.queue)
+            .field("path", &self.path)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> Frontier<T> for Durable<T>
+where
+    T: Serialize + DeserializeOwned,
+{
+    fn push(&mut self, item: T) {
+        if let Err(error) = self.try_push(item) {
+            panic!("cannot write to {}: {error}", self.path.display());
+        }
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        match self.try_pop() {
+            Ok(item) => item,
+            Err(error) => panic!("cannot write to {}: {error}", self.path.display()),
+        }
+    }
+
+    fn pop_oldest(&mut self) -> Option<T> {
+        self.pop()
+    }
+
+    fn front_inserted(&mut self, n: usize, item: &T) {
+        if let Err(error) = self.insert_front(n, item) {
+            panic!("cannot write to {}: {error}", self.path.display());
+        }
+    }
+
+    fn front_removed(&mut self, n: usize) {
+        if let Err(error) = self.remove_front(n) {
+            panic!("cannot write to {}: {error}", self.path.display());
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Remove all elements by compacting the log.
+    fn clear(&mut self) {
+        self.queue.clear();
+        if let Err(error) = self.compact() {
+            panic!("cannot compact {}: {error}", self.path.display());
+        }
+    }
+}
+
+/// Removing elements compacts the log.
+impl<T> InspectFrontier<T> for Durable<T>
+where
+    T: Serialize + DeserializeOwned,
+{
+    type Iter<'a>
+        = vec_deque::Iter<'a, T>
+    where
+        T: 'a;
+
+    fn iter(&self) -> Self::Iter<'_> {
+        self.queue.iter()
+    }
+
+    fn retain<P>(&mut self, f: P)
+    where
+        P: FnMut(&T) -> bool,
+    {
+        self.queue.retain(f);
+        if let Err(error) = self.compact() {
+            panic!("cannot compact {}: {error}", self.path.display());
+        }
+    }
+}
+
+/// Encode a record with the given kind and payload.
+fn encode(kind: u8, payload: &[u8]) -> io::Result<Vec<u8>> {
+    let len = u32::try_from(payload.len()).map_err(invalid_data)?;
+    let mut record = Vec::with_capacity(HEADER_LEN + payload.len());
+    record.push(kind);
+    record.extend(len.to_le_bytes());
+    record.extend(checksum(kind, len, payload).to_le_bytes());
+    record.extend(payload);
+    Ok(record)
+}
+
+/// Decode the record at the start of the given bytes into its kind and payload, if complete and
+/// valid.
+fn decode(bytes: &[u8]) -> Option<(u8, &[u8])> {
+    let header = bytes.get(..HEADER_LEN)?;
+    let kind = header[0];
+    let len = u32::from_le_bytes(header[1..5].try_into().ok()?);
+    let sum = u32::from_le_bytes(header[5..9].try_into().ok()?);
+    let payload = bytes.get(HEADER_LEN..HEADER_LEN + len as usize)?;
+    (checksum(kind, len, payload) == sum).then_some((kind, payload))
+}
+
+/// Encode the given position for a consume or insert record.
+fn position(n: usize) -> Vec<u8> {
+    (n as u64).to_le_bytes().to_vec()
+}
+
+/// Split the position off the payload of a consume or insert record.
+fn split_position(payload: &[u8]) -> Option<(usize, &[u8])> {
+    let n = u64::from_le_bytes(payload.get(..POSITION_LEN)?.try_into().ok()?);
+    Some((usize::try_from(n).ok()?, &payload[POSITION_LEN..]))
+}
+
+/// Whether the invalid record at the start of the given bytes is a torn final one, i.e. its header
+/// is incomplete, or it reaches the end of the log according to its length and no valid record
+/// follows. As the length may be corrupt itself, the remaining bytes are scanned for a valid
+/// record instead of trusting it.
+fn is_torn(bytes: &[u8]) -> bool {
+    let Some(len) = bytes.get(1..5) else {
+        return true;
+    };
+    let len = u32::from_le_bytes(len.try_into().expect("4 bytes")) as usize;
+    HEADER_LEN.saturating_add(len) >= bytes.len()
+        && !(HEADER_LEN..bytes.len()).any(|offset| decode(&bytes[offset..]).is_some())
+}
+
+/// Flush the directory containing the given path to disk, such that a rename is durable.
+#[cfg(unix)]
+fn sync_dir(path: &Path) -> io::Result<()> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    File::open(dir)?.sync_all()
+}
+
+/// Directories cannot be opened, hence not be flushed, on other platforms.
+#[cfg(not(unix))]
+fn sync_dir(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+fn checksum(kind: u8, len: u32, payload: &[u8]) -> u32 {
+    let mut hasher = crc32fast::Hasher::new();
+    hasher.update(&[kind]);
+    hasher.update(&len.to_le_bytes());
+    hasher.update(payload);
+    hasher.finalize()
+}
+
+fn invalid_data<E>(error: E) -> io::Error
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::IteratorExt;
+    use tempfile::tempdir;
+
+    fn pending(path: &Path) -> Vec<String> {
+        let durable = Durable::<String>::open(path).unwrap();
+        durable.iter().cloned().collect()
+    }
+
+    #[test]
+    fn test() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("queue.log");
+
+        let durable = Durable::open(&path).unwrap();
+        let mut words = ["a"].map(String::from).queue_iter_with(durable);
+        for word in ["b", "c", "d"] {
+            words.enqueue(word.to_string());
+        }
+        assert_eq!(words.next().as_deref(), Some("a"));
+        assert_eq!(words.next().as_deref(), Some("b"));
+        drop(words);
+        assert_eq!(pending(&path), ["c", "d"]);
+
+        let durable = Durable::open(&path).unwrap();
+        let mut words = None.queue_iter_with(durable);
+        words.enqueue(String::from("e"));
+        assert_eq!(words.next().as_deref(), Some("c"));
+        words.retain(|word| word != "d");
+        drop(words);
+        assert_eq!(pending(&path), ["e"]);
+
+        // Clearing compacts the log instead of consuming each element.
+        let durable = Durable::open(&path).unwrap();
+        let mut words = None.queue_iter_with(durable);
+        words.extend(["f", "g", "h"].map(String::from));
+        words.clear_pending();
+        drop(words);
+        assert_eq!(fs::metadata(&path).unwrap().len(), 0);
+    }
+
+    #[test]
+    fn test_front() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("queue.log");
+        let open = || None.queue_iter_with(Durable::open(&path).unwrap().with_compact_after(2));
+
+        // Peeked elements remain pending.
+        let mut words = open();
+        words.extend(["a", "b"].map(String::from));
+        assert_eq!(words.peek().map(String::as_str), Some("a"));
+        drop(words);
+        assert_eq!(pending(&path), ["a", "b"]);
+
+        // As do elements put back after being yielded.
+        let mut words = open();
+        let word = words.next().unwrap();
+        words.unget(word);
+        drop(words);
+        assert_eq!(pending(&path), ["a", "b"]);
+
+        let mut words = open();
+        words.push_front(String::from("x"));
+        assert_eq!(words.peek_nth(2).map(String::as_str), Some("b"));
+        assert_eq!(words.next().as_deref(), Some("x"));
+        words.retain(|word| word != "a");
+        words.enqueue(String::from("c"));
+        drop(words);
+        assert_eq!(pending(&path), ["b", "c"]);
+
+        // Interleave peeking, yielding and compacting.
+        let mut words = open();
+        let mut expected = vec![String::from("b"), String::from("c")];
+        for n in 0..10 {
+            words.enqueue(n.to_string());
+            expected.push(n.to_string());
+            if n % 2 == 0 {
+                assert_eq!(words.peek_nth(1), expected.get(1));
+                assert_eq!(words.next().as_ref(), expected.first());
+                expected.remove(0);
+            }
+        }
+        drop(words);
+        assert_eq!(pending(&path), expected);
+
+        let mut words = open();
+        assert_eq!(words.peek(), expected.first());
+        words.clear_pending();
+        drop(words);
+        assert_eq!(fs::metadata(&path).unwrap().len(), 0);
+    }
+
+    #[test]
+    fn test_compact() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("queue.log");
+
+        let mut durable = Durable::open(&path).unwrap().with_compact_after(4);
+        for n in 0..10 {
+            durable.try_push(n.to_string()).unwrap();
+        }
+        let len = fs::metadata(&path).unwrap().len();
+
+        // Compaction requires as many consumed as pending elements.
+        for n in 0..5 {
+            assert_eq!(durable.try_pop().unwrap(), Some(n.to_string()));
+        }
+        assert!(fs::metadata(&path).unwrap().len() > len);
+        assert_eq!(durable.try_pop().unwrap(), Some(5.to_string()));
+        assert!(fs::metadata(&path).unwrap().len() < len);
+        assert_eq!(durable.consumed, 1);
+
+        durable.try_push(10.to_string()).unwrap();
+        drop(durable);
+        let expected = (6..11).map(|n| n.to_string()).collect::<Vec<_>>();
+        assert_eq!(pending(&path), expected);
+    }
+
+    #[test]
+    fn test_torn() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("queue.log");
+
+        let mut durable = Durable::open(&path).unwrap();
+        for word in ["a", "b", "c"] {
+            durable.try_push(word.to_string()).unwrap();
+        }
+        drop(durable);
+        let len = fs::metadata(&path).unwrap().len();
+
+        // Truncated final record, e.g. a crash during a write.
+        let file = OpenOptions::new().write(true).open(&path).unwrap();
+        file.set_len(len - 3).unwrap();
+        assert_eq!(pending(&path), ["a", "b"]);
+
+        // The torn record has been truncated, hence further records can be appended.
+        let mut durable = Durable::open(&path).unwrap();
+        durable.try_push(String::from("d")).unwrap();
+        drop(durable);
+        assert_eq!(pending(&path), ["a", "b", "d"]);
+
+        // Truncated header.
+        let len = fs::metadata(&path).unwrap().len();
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(&[ENQUEUE, 42]).unwrap();
+        assert_eq!(pending(&path), ["a", "b", "d"]);
+        assert_eq!(fs::metadata(&path).unwrap().len(), len);
+
+        // Torn final record with garbage payload.
+        let mut bytes = fs::read(&path).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xff;
+        fs::write(&path, &bytes).unwrap();
+        assert_eq!(pending(&path), ["a", "b"]);
+
+        // Corrupt length of a record followed by further ones, which must not be mistaken for a
+        // torn final record reaching the end of the log.
+        let bytes = fs::read(&path).unwrap();
+        let mut corrupt = bytes.clone();
+        corrupt[4] ^= 0xff;
+        fs::write(&path, &corrupt).unwrap();
+        let error = Durable::<String>::open(&path).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(fs::read(&path).unwrap(), corrupt);
+        fs::write(&path, &bytes).unwrap();
+
+        // Corrupt record followed by further ones.
+        let mut bytes = fs::read(&path).unwrap();
+        bytes[HEADER_LEN] ^= 0xff;
+        fs::write(&path, &bytes).unwrap();
+        let error = Durable::<String>::open(&path).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+}
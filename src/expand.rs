@@ -0,0 +1,85 @@
+use crate::{queue_iter, QueueIter};
+
+/// Create an `Iterator` yielding the initial elements and, recursively in breadth-first order, the
+/// children returned by the given function for each yielded element.
+///
+/// # Examples
+///
+/// ```
+/// use enqueue::expand;
+///
+/// let i = expand([1], |&n| if n < 4 { vec![2 * n, 2 * n + 1] } else { vec![] });
+///
+/// let items = i.collect::<Vec<_>>();
+/// assert_eq!(items, [1, 2, 3, 4, 5, 6, 7]);
+/// ```
+pub fn expand<I, F, J>(initial: I, f: F) -> Expand<I::IntoIter, F>
+where
+    I: IntoIterator,
+    F: FnMut(&I::Item) -> J,
+    J: IntoIterator<Item = I::Item>,
+{
+    Expand {
+        queue: queue_iter(initial),
+        f,
+    }
+}
+
+/// An `Iterator` yielding the initial elements and, recursively in breadth-first order, the
+/// children returned by a function for each yielded element. Built on [QueueIter].
+pub struct Expand<I, F>
+where
+    I: Iterator,
+{
+    queue: QueueIter<I>,
+    f: F,
+}
+
+impl<I, F, J> Iterator for Expand<I, F>
+where
+    I: Iterator,
+    F: FnMut(&I::Item) -> J,
+    J: IntoIterator<Item = I::Item>,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.queue.next()?;
+        self.queue.extend((self.f)(&item));
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.queue.size_hint().0, None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test() {
+        let graph = HashMap::from([("a", vec!["b", "c"]), ("b", vec!["d"]), ("c", vec!["d"])]);
+
+        let paths = expand([vec!["a"]], |path: &Vec<_>| {
+            let last = path.last().unwrap();
+            graph
+                .get(last)
+                .into_iter()
+                .flatten()
+                .map(|next| {
+                    let mut path = path.clone();
+                    path.push(*next);
+                    path
+                })
+                .collect::<Vec<_>>()
+        })
+        .filter(|path| path.last() == Some(&"d"))
+        .map(|path| path.concat())
+        .collect::<Vec<_>>();
+
+        assert_eq!(paths, ["abd", "acd"]);
+    }
+}
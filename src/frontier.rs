@@ -0,0 +1,457 @@
+use std::{
+    collections::{hash_map::RandomState, vec_deque, VecDeque},
+    fmt::{self, Debug, Formatter},
+    hash::{BuildHasher, Hasher},
+    iter::{self, Rev},
+    slice,
+};
+
+/// Strategy defining in which order the enqueued elements of a [QueueIter](crate::QueueIter) are
+/// yielded.
+pub trait Frontier<T> {
+    /// Add an element.
+    fn push(&mut self, item: T);
+
+    /// Remove the element to be yielded next, if any.
+    fn pop(&mut self) -> Option<T>;
+
+    /// Remove the earliest pushed element, if any, regardless of the order elements are yielded
+    /// in, see [Overflow::DropOldest](crate::Overflow::DropOldest).
+    fn pop_oldest(&mut self) -> Option<T>;
+
+    /// The number of elements. Frontiers producing elements lazily, e.g. [LazyFifo], may only
+    /// return a lower bound, but must not return zero if there are elements.
+    fn len(&self) -> usize;
+
+    /// Whether there are no elements.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Remove all elements. The default implementation pops all elements, hence implementations
+    /// should override it if they can do better.
+    fn clear(&mut self) {
+        while self.pop().is_some() {}
+    }
+
+    /// Notify that the given element has been buffered at the given position among the enqueued
+    /// elements buffered at the front of a [QueueIter](crate::QueueIter), which are yielded before
+    /// the elements of this frontier, i.e. it has been popped for peeking or put back. Frontiers
+    /// persisting their elements, e.g. `Durable`, keep buffered elements pending until they are
+    /// removed, see [Frontier::front_removed]. Does nothing by default.
+    fn front_inserted(&mut self, _n: usize, _item: &T) {}
+
+    /// Notify that the element at the given position among the enqueued elements buffered at the
+    /// front of a [QueueIter](crate::QueueIter) has been yielded or removed, see
+    /// [Frontier::front_inserted]. Does nothing by default.
+    fn front_removed(&mut self, _n: usize) {}
+}
+
+/// [Frontier] allowing for inspecting and removing its elements.
+pub trait InspectFrontier<T>: Frontier<T> {
+    /// `Iterator` over references to the elements.
+    type Iter<'a>: Iterator<Item = &'a T>
+    where
+        Self: 'a,
+        T: 'a;
+
+    /// Iterate over references to the elements, in the order they would be popped if possible.
+    fn iter(&self) -> Self::Iter<'_>;
+
+    /// Only retain the elements for which the given predicate returns `true`.
+    fn retain<P>(&mut self, f: P)
+    where
+        P: FnMut(&T) -> bool;
+}
+
+/// [InspectFrontier] additionally allowing for mutating its elements in place.
+pub trait InspectFrontierMut<T>: InspectFrontier<T> {
+    /// `Iterator` over mutable references to the elements.
+    type IterMut<'a>: Iterator<Item = &'a mut T>
+    where
+        Self: 'a,
+        T: 'a;
+
+    /// Iterate over mutable references to the elements, in the order they would be popped if
+    /// possible.
+    fn iter_mut(&mut self) -> Self::IterMut<'_>;
+}
+
+/// [Frontier] yielding elements in FIFO order, e.g. for breadth-first traversals.
+#[derive(Debug, Clone)]
+pub struct Fifo<T>(VecDeque<T>);
+
+impl<T> Default for Fifo<T> {
+    fn default() -> Self {
+        Self(VecDeque::default())
+    }
+}
+
+impl<T> Frontier<T> for Fifo<T> {
+    fn push(&mut self, item: T) {
+        self.0.push_back(item)
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        self.0.pop_front()
+    }
+
+    fn pop_oldest(&mut self) -> Option<T> {
+        self.0.pop_front()
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn clear(&mut self) {
+        self.0.clear()
+    }
+}
+
+impl<T> InspectFrontier<T> for Fifo<T> {
+    type Iter<'a>
+        = vec_deque::Iter<'a, T>
+    where
+        T: 'a;
+
+    fn iter(&self) -> Self::Iter<'_> {
+        self.0.iter()
+    }
+
+    fn retain<P>(&mut self, f: P)
+    where
+        P: FnMut(&T) -> bool,
+    {
+        self.0.retain(f)
+    }
+}
+
+impl<T> InspectFrontierMut<T> for Fifo<T> {
+    type IterMut<'a>
+        = vec_deque::IterMut<'a, T>
+    where
+        T: 'a;
+
+    fn iter_mut(&mut self) -> Self::IterMut<'_> {
+        self.0.iter_mut()
+    }
+}
+
+/// [Frontier] yielding elements in FIFO order like [Fifo], but additionally allowing for pushing
+/// whole iterators which are only pulled from lazily when their turn comes, see
+/// [QueueIter::enqueue_iter](crate::QueueIter::enqueue_iter). Hence memory is proportional to the
+/// number of pushed iterators rather than to the number of their elements.
+///
+/// As pushed iterators are boxed trait objects, this frontier is neither `Clone` nor `Send`. Its
+/// length is only a lower bound, i.e. the sum of the lower bounds of the `size_hint`s of the pushed
+/// iterators, saturating at `usize::MAX`.
+pub struct LazyFifo<'a, T>(VecDeque<LazySource<'a, T>>);
+
+struct LazySource<'a, T> {
+    head: T,
+    rest: Box<dyn Iterator<Item = T> + 'a>,
+}
+
+impl<'a, T> LazyFifo<'a, T> {
+    /// Push all elements of the given iterator, which is only pulled from when its turn comes.
+    pub fn push_iter<J>(&mut self, items: J)
+    where
+        J: IntoIterator<Item = T>,
+        J::IntoIter: 'a,
+    {
+        let mut rest = items.into_iter();
+        // Pull the first element eagerly such that there are no empty sources.
+        if let Some(head) = rest.next() {
+            let rest = Box::new(rest);
+            self.0.push_back(LazySource { head, rest })
+        }
+    }
+}
+
+impl<'a, T> Default for LazyFifo<'a, T> {
+    fn default() -> Self {
+        Self(VecDeque::default())
+    }
+}
+
+impl<'a, T> Debug for LazyFifo<'a, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LazyFifo")
+            .field("sources", &self.0.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a, T> Frontier<T> for LazyFifo<'a, T>
+where
+    T: 'a,
+{
+    fn push(&mut self, item: T) {
+        let rest = Box::new(iter::empty());
+        self.0.push_back(LazySource { head: item, rest })
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        let LazySource { head, mut rest } = self.0.pop_front()?;
+        if let Some(next) = rest.next() {
+            self.0.push_front(LazySource { head: next, rest });
+        }
+        Some(head)
+    }
+
+    fn pop_oldest(&mut self) -> Option<T> {
+        self.pop()
+    }
+
+    fn len(&self) -> usize {
+        self.0
+            .iter()
+            .map(|source| source.rest.size_hint().0.saturating_add(1))
+            .fold(0, usize::saturating_add)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Remove all elements by dropping the pushed iterators without pulling from them.
+    fn clear(&mut self) {
+        self.0.clear()
+    }
+}
+
+/// [Frontier] yielding elements in LIFO order, e.g. for depth-first traversals.
+#[derive(Debug, Clone)]
+pub struct Lifo<T>(VecDeque<T>);
+
+impl<T> Default for Lifo<T> {
+    fn default() -> Self {
+        Self(VecDeque::default())
+    }
+}
+
+impl<T> Frontier<T> for Lifo<T> {
+    fn push(&mut self, item: T) {
+        self.0.push_back(item)
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        self.0.pop_back()
+    }
+
+    fn pop_oldest(&mut self) -> Option<T> {
+        self.0.pop_front()
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn clear(&mut self) {
+        self.0.clear()
+    }
+}
+
+impl<T> InspectFrontier<T> for Lifo<T> {
+    type Iter<'a>
+        = Rev<vec_deque::Iter<'a, T>>
+    where
+        T: 'a;
+
+    fn iter(&self) -> Self::Iter<'_> {
+        self.0.iter().rev()
+    }
+
+    fn retain<P>(&mut self, f: P)
+    where
+        P: FnMut(&T) -> bool,
+    {
+        self.0.retain(f)
+    }
+}
+
+impl<T> InspectFrontierMut<T> for Lifo<T> {
+    type IterMut<'a>
+        = Rev<vec_deque::IterMut<'a, T>>
+    where
+        T: 'a;
+
+    fn iter_mut(&mut self) -> Self::IterMut<'_> {
+        self.0.iter_mut().rev()
+    }
+}
+
+/// [Frontier] yielding elements in random order, using a fast, non-cryptographic pseudo random
+/// number generator.
+#[derive(Debug, Clone)]
+pub struct Random<T> {
+    items: Vec<T>,
+
+    /// The push sequence number of each element, for [Frontier::pop_oldest].
+    seqs: Vec<u64>,
+    seq: u64,
+    state: u64,
+}
+
+impl<T> Random<T> {
+    /// Create a [Random] frontier with a random seed.
+    pub fn new() -> Self {
+        Self::with_seed(RandomState::new().build_hasher().finish())
+    }
+
+    /// Create a [Random] frontier with the given seed, yielding elements in a reproducible order.
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            items: Vec::default(),
+            seqs: Vec::default(),
+            seq: 0,
+            // Xorshift must not be seeded with zero.
+            state: seed.max(1),
+        }
+    }
+
+    /// Xorshift64*.
+    fn next_random(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+}
+
+impl<T> Default for Random<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Frontier<T> for Random<T> {
+    fn push(&mut self, item: T) {
+        self.items.push(item);
+        self.seqs.push(self.seq);
+        self.seq += 1;
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        if self.items.is_empty() {
+            return None;
+        }
+        let n = (self.next_random() % self.items.len() as u64) as usize;
+        self.seqs.swap_remove(n);
+        Some(self.items.swap_remove(n))
+    }
+
+    /// Takes linear time.
+    fn pop_oldest(&mut self) -> Option<T> {
+        let (n, _) = self.seqs.iter().enumerate().min_by_key(|(_, seq)| **seq)?;
+        self.seqs.swap_remove(n);
+        Some(self.items.swap_remove(n))
+    }
+
+    fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    fn clear(&mut self) {
+        self.items.clear();
+        self.seqs.clear();
+    }
+}
+
+impl<T> InspectFrontier<T> for Random<T> {
+    type Iter<'a>
+        = slice::Iter<'a, T>
+    where
+        T: 'a;
+
+    fn iter(&self) -> Self::Iter<'_> {
+        self.items.iter()
+    }
+
+    fn retain<P>(&mut self, mut f: P)
+    where
+        P: FnMut(&T) -> bool,
+    {
+        (self.items, self.seqs) = self
+            .items
+            .drain(..)
+            .zip(self.seqs.drain(..))
+            .filter(|(item, _)| f(item))
+            .unzip();
+    }
+}
+
+impl<T> InspectFrontierMut<T> for Random<T> {
+    type IterMut<'a>
+        = slice::IterMut<'a, T>
+    where
+        T: 'a;
+
+    fn iter_mut(&mut self) -> Self::IterMut<'_> {
+        self.items.iter_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test() {
+        let mut fifo = Fifo::default();
+        let mut lifo = Lifo::default();
+        for n in 0..3 {
+            fifo.push(n);
+            lifo.push(n);
+        }
+        assert_eq!(fifo.len(), 3);
+        assert_eq!(lifo.len(), 3);
+        assert_eq!(
+            std::iter::from_fn(|| fifo.pop()).collect::<Vec<_>>(),
+            [0, 1, 2]
+        );
+        assert_eq!(
+            std::iter::from_fn(|| lifo.pop()).collect::<Vec<_>>(),
+            [2, 1, 0]
+        );
+        assert!(fifo.is_empty());
+        assert!(lifo.is_empty());
+
+        for n in 0..3 {
+            lifo.push(n);
+        }
+        assert_eq!(lifo.pop_oldest(), Some(0));
+        assert_eq!(lifo.pop(), Some(2));
+
+        let mut lazy = LazyFifo::default();
+        lazy.push(0);
+        lazy.push_iter(1..3);
+        lazy.push_iter(None);
+        lazy.push(3);
+        assert_eq!(lazy.len(), 4);
+        assert_eq!(
+            std::iter::from_fn(|| lazy.pop()).collect::<Vec<_>>(),
+            [0, 1, 2, 3]
+        );
+        assert!(lazy.is_empty());
+
+        let mut random = Random::with_seed(42);
+        for n in 0..100 {
+            random.push(n);
+        }
+        let mut numbers = std::iter::from_fn(|| random.pop()).collect::<Vec<_>>();
+        assert_ne!(numbers, (0..100).collect::<Vec<_>>());
+        numbers.sort();
+        assert_eq!(numbers, (0..100).collect::<Vec<_>>());
+
+        for n in 0..10 {
+            random.push(n);
+        }
+        random.retain(|n| n % 3 != 0);
+        random.pop();
+        let oldest = std::iter::from_fn(|| random.pop_oldest()).collect::<Vec<_>>();
+        assert_eq!(oldest.len(), 5);
+        assert!(oldest.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+}
@@ -0,0 +1,166 @@
+use crate::{queue_iter_with, Fifo, Frontier, QueueIter};
+use std::{
+    cell::RefCell,
+    fmt::{self, Debug, Formatter},
+    marker::PhantomData,
+    rc::Rc,
+};
+
+/// Create an `Iterator` allowing for enqueuing elements to its end together with a cloneable
+/// [Enqueuer] handle, which can be used to enqueue elements from anywhere, e.g. from within
+/// adapter closures, while the `Iterator` is being consumed.
+///
+/// # Examples
+///
+/// ```
+/// use enqueue::queue_iter_with_handle;
+///
+/// let i = std::iter::once(666);
+/// let (i, enqueuer) = queue_iter_with_handle(i);
+///
+/// let items = i
+///     .inspect(|&n| {
+///         if n == 666 {
+///             enqueuer.enqueue(42);
+///         }
+///     })
+///     .collect::<Vec<_>>();
+/// assert_eq!(items, [666, 42]);
+/// ```
+pub fn queue_iter_with_handle<I>(initial: I) -> (SharedQueueIter<I::IntoIter>, Enqueuer<I::Item>)
+where
+    I: IntoIterator,
+{
+    let frontier = Shared::new(Fifo::default());
+    let enqueuer = frontier.enqueuer();
+    (queue_iter_with(initial, frontier), enqueuer)
+}
+
+/// A [QueueIter] with a [Shared] [Fifo] frontier, see [queue_iter_with_handle].
+pub type SharedQueueIter<I> = QueueIter<I, Shared<Fifo<<I as Iterator>::Item>>>;
+
+/// [Frontier] wrapping another one such that it can be shared with [Enqueuer] handles. Not
+/// thread-safe.
+pub struct Shared<F>(Rc<RefCell<F>>);
+
+impl<F> Shared<F> {
+    /// Wrap the given [Frontier].
+    pub fn new(frontier: F) -> Self {
+        Self(Rc::new(RefCell::new(frontier)))
+    }
+
+    /// Create an [Enqueuer] handle for this frontier.
+    pub fn enqueuer<T>(&self) -> Enqueuer<T, F>
+    where
+        F: Frontier<T>,
+    {
+        Enqueuer {
+            frontier: self.0.clone(),
+            _item: PhantomData,
+        }
+    }
+}
+
+impl<F> Debug for Shared<F>
+where
+    F: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Shared").field(&self.0.borrow()).finish()
+    }
+}
+
+impl<T, F> Frontier<T> for Shared<F>
+where
+    F: Frontier<T>,
+{
+    fn push(&mut self, item: T) {
+        self.0.borrow_mut().push(item)
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        self.0.borrow_mut().pop()
+    }
+
+    fn pop_oldest(&mut self) -> Option<T> {
+        self.0.borrow_mut().pop_oldest()
+    }
+
+    fn len(&self) -> usize {
+        self.0.borrow().len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0.borrow().is_empty()
+    }
+
+    fn clear(&mut self) {
+        self.0.borrow_mut().clear()
+    }
+
+    fn front_inserted(&mut self, n: usize, item: &T) {
+        self.0.borrow_mut().front_inserted(n, item)
+    }
+
+    fn front_removed(&mut self, n: usize) {
+        self.0.borrow_mut().front_removed(n)
+    }
+}
+
+/// Cloneable handle for enqueuing elements to a [Shared] frontier, e.g. of a [QueueIter] created
+/// with [queue_iter_with_handle]. Not thread-safe.
+pub struct Enqueuer<T, F = Fifo<T>> {
+    frontier: Rc<RefCell<F>>,
+    _item: PhantomData<fn(T)>,
+}
+
+impl<T, F> Enqueuer<T, F>
+where
+    F: Frontier<T>,
+{
+    /// Enqueue an element, by default to the end of the `Iterator`.
+    pub fn enqueue(&self, item: T) {
+        self.frontier.borrow_mut().push(item)
+    }
+}
+
+impl<T, F> Clone for Enqueuer<T, F> {
+    fn clone(&self) -> Self {
+        Self {
+            frontier: self.frontier.clone(),
+            _item: PhantomData,
+        }
+    }
+}
+
+impl<T, F> Debug for Enqueuer<T, F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Enqueuer").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test() {
+        let (numbers, enqueuer) = queue_iter_with_handle(0..10);
+
+        let sum = numbers
+            .filter(|&n| n != 7)
+            .map({
+                let enqueuer = enqueuer.clone();
+                move |n| {
+                    if n < 5 {
+                        enqueuer.enqueue(20);
+                    }
+                    n
+                }
+            })
+            .sum::<i32>();
+
+        // The sum of 0..10 without 7 is 38 and we enqueue 5 times 20, i.e. get another 100.
+        assert_eq!(sum, 138);
+    }
+}
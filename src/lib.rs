@@ -1,6 +1,57 @@
-use std::collections::VecDeque;
+mod byte_size;
+#[cfg(feature = "stream")]
+mod concurrent;
+mod dedup;
+mod depth;
+#[cfg(feature = "durable")]
+mod durable;
+mod expand;
+mod frontier;
+mod handle;
+#[cfg(feature = "rayon")]
+mod par_expand;
+#[cfg(feature = "parallel")]
+mod parallel;
+mod priority;
+#[cfg(feature = "spill")]
+mod spill;
+mod stack;
+#[cfg(feature = "stream")]
+mod stream;
+mod sync;
 
-/// Extension method `queue_iter` for any type implementing `IntoIterator`.
+pub use byte_size::ByteSize;
+#[cfg(feature = "stream")]
+pub use concurrent::run_concurrent;
+pub use dedup::{dedup_queue_iter, dedup_queue_iter_by_key, DedupQueueIter, Identity};
+pub use depth::{depth_queue_iter, DepthQueueIter};
+#[cfg(feature = "durable")]
+pub use durable::Durable;
+pub use expand::{expand, Expand};
+pub use frontier::{Fifo, Frontier, InspectFrontier, InspectFrontierMut, LazyFifo, Lifo, Random};
+pub use handle::{queue_iter_with_handle, Enqueuer, Shared, SharedQueueIter};
+#[cfg(feature = "rayon")]
+pub use par_expand::{par_expand, ParExpand};
+#[cfg(feature = "parallel")]
+pub use parallel::{run_parallel, ParallelEnqueuer};
+pub use priority::{
+    priority_queue_iter, priority_queue_iter_by, priority_queue_iter_by_key, ByKey, Compare,
+    NaturalOrder, Priority, PriorityIter, PriorityQueueIter,
+};
+#[cfg(feature = "spill")]
+pub use spill::Spill;
+pub use stack::{stack_iter, StackIter};
+use std::{
+    collections::VecDeque,
+    error::Error,
+    fmt::{self, Debug, Display, Formatter},
+    hash::Hash,
+};
+#[cfg(feature = "stream")]
+pub use stream::{queue_stream, QueueStream, StreamEnqueuer, TryEnqueueError};
+pub use sync::{sync_queue_iter, Closed, SyncEnqueuer, SyncQueueIter};
+
+/// Extension methods `queue_iter` and friends for any type implementing `IntoIterator`.
 pub trait IteratorExt
 where
     Self: IntoIterator + Sized,
@@ -24,6 +75,186 @@ where
     /// assert_eq!(i.next(), Some(42));
     /// ```
     fn queue_iter(self) -> QueueIter<Self::IntoIter>;
+
+    /// Create an `Iterator` allowing for enqueuing elements which are yielded in the order defined
+    /// by the given [Frontier].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use enqueue::{IteratorExt, Lifo};
+    ///
+    /// let i = std::iter::once(666);
+    /// let mut i = i.queue_iter_with(Lifo::default());
+    ///
+    /// i.enqueue(1);
+    /// i.enqueue(42);
+    /// assert_eq!(i.next(), Some(666));
+    /// assert_eq!(i.next(), Some(42));
+    /// assert_eq!(i.next(), Some(1));
+    /// assert_eq!(i.next(), None);
+    /// ```
+    fn queue_iter_with<F>(self, frontier: F) -> QueueIter<Self::IntoIter, F>
+    where
+        F: Frontier<Self::Item>;
+
+    /// Create an `Iterator` allowing for enqueuing elements which are yielded in priority order,
+    /// i.e. greatest first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use enqueue::IteratorExt;
+    ///
+    /// let i = std::iter::once(666);
+    /// let mut i = i.priority_queue_iter();
+    ///
+    /// i.enqueue(1);
+    /// i.enqueue(42);
+    /// assert_eq!(i.next(), Some(666));
+    /// assert_eq!(i.next(), Some(42));
+    /// assert_eq!(i.next(), Some(1));
+    /// assert_eq!(i.next(), None);
+    /// ```
+    fn priority_queue_iter(self) -> PriorityQueueIter<Self::IntoIter>
+    where
+        Self::Item: Ord;
+
+    /// Create an `Iterator` allowing for enqueuing elements which are yielded in priority order of
+    /// the keys extracted by the given function, i.e. greatest key first.
+    fn priority_queue_iter_by_key<F, K>(self, f: F) -> PriorityQueueIter<Self::IntoIter, ByKey<F>>
+    where
+        F: FnMut(&Self::Item) -> K,
+        K: Ord;
+
+    /// Create an `Iterator` allowing for enqueuing elements which are yielded in priority order
+    /// defined by the given comparator, i.e. greatest first.
+    fn priority_queue_iter_by<C>(self, compare: C) -> PriorityQueueIter<Self::IntoIter, C>
+    where
+        C: Compare<Self::Item>;
+
+    /// Create an `Iterator` allowing for enqueuing elements which are yielded in LIFO order, i.e.
+    /// most recently enqueued first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use enqueue::IteratorExt;
+    ///
+    /// let i = std::iter::once(666);
+    /// let mut i = i.stack_iter();
+    ///
+    /// i.enqueue(1);
+    /// i.enqueue(42);
+    /// assert_eq!(i.next(), Some(666));
+    /// assert_eq!(i.next(), Some(42));
+    /// assert_eq!(i.next(), Some(1));
+    /// assert_eq!(i.next(), None);
+    /// ```
+    fn stack_iter(self) -> StackIter<Self::IntoIter>;
+
+    /// Create an `Iterator` yielding the elements of this one and, recursively in breadth-first
+    /// order, the children returned by the given function for each yielded element.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use enqueue::IteratorExt;
+    ///
+    /// let i = [1].expand(|&n| if n < 4 { vec![2 * n, 2 * n + 1] } else { vec![] });
+    ///
+    /// let items = i.filter(|n| n % 2 == 1).collect::<Vec<_>>();
+    /// assert_eq!(items, [1, 3, 5, 7]);
+    /// ```
+    fn expand<F, J>(self, f: F) -> Expand<Self::IntoIter, F>
+    where
+        F: FnMut(&Self::Item) -> J,
+        J: IntoIterator<Item = Self::Item>;
+
+    /// Create an `Iterator` allowing for enqueuing elements to its end, silently dropping elements
+    /// which have already been seen.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use enqueue::IteratorExt;
+    ///
+    /// let i = std::iter::once(666);
+    /// let mut i = i.dedup_queue_iter();
+    ///
+    /// i.enqueue(666);
+    /// i.enqueue(42);
+    /// i.enqueue(42);
+    /// assert_eq!(i.next(), Some(666));
+    /// assert_eq!(i.next(), Some(42));
+    /// assert_eq!(i.next(), None);
+    /// ```
+    fn dedup_queue_iter(self) -> DedupQueueIter<Self::IntoIter, Self::Item, Identity<Self::Item>>
+    where
+        Self::Item: Clone + Hash + Eq;
+
+    /// Create an `Iterator` allowing for enqueuing elements to its end, silently dropping elements
+    /// with keys, extracted by the given function, which have already been seen.
+    fn dedup_queue_iter_by_key<K, F>(self, key: F) -> DedupQueueIter<Self::IntoIter, K, F>
+    where
+        K: Hash + Eq,
+        F: FnMut(&Self::Item) -> K;
+
+    /// Create an `Iterator` allowing for enqueuing elements to its end, tracking the depth of each
+    /// element.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use enqueue::IteratorExt;
+    ///
+    /// let i = std::iter::once(666);
+    /// let mut i = i.depth_queue_iter();
+    ///
+    /// assert_eq!(i.next(), Some(666));
+    /// i.enqueue(42);
+    /// assert_eq!(i.next_with_depth(), Some((1, 42)));
+    /// ```
+    fn depth_queue_iter(self) -> DepthQueueIter<Self::IntoIter>;
+
+    /// Create an `Iterator` allowing for enqueuing elements to its end together with a cloneable
+    /// [Enqueuer] handle, which can be used to enqueue elements from anywhere.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use enqueue::IteratorExt;
+    ///
+    /// let (i, enqueuer) = (0..3).queue_iter_with_handle();
+    ///
+    /// let items = i
+    ///     .map(|n| {
+    ///         if n == 1 {
+    ///             enqueuer.enqueue(42);
+    ///         }
+    ///         n
+    ///     })
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(items, [0, 1, 2, 42]);
+    /// ```
+    fn queue_iter_with_handle(self) -> (SharedQueueIter<Self::IntoIter>, Enqueuer<Self::Item>);
+
+    /// Create an `Iterator` allowing for enqueuing elements to its end together with a cloneable,
+    /// thread-safe [SyncEnqueuer] handle; `next` blocks while waiting for further elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use enqueue::IteratorExt;
+    ///
+    /// let (i, enqueuer) = (0..3).sync_queue_iter();
+    ///
+    /// enqueuer.enqueue(42).unwrap();
+    /// drop(enqueuer);
+    /// let items = i.collect::<Vec<_>>();
+    /// assert_eq!(items, [0, 1, 2, 42]);
+    /// ```
+    fn sync_queue_iter(self) -> (SyncQueueIter<Self::IntoIter>, SyncEnqueuer<Self::Item>);
 }
 
 impl<T> IteratorExt for T
@@ -33,6 +264,74 @@ where
     fn queue_iter(self) -> QueueIter<Self::IntoIter> {
         queue_iter(self)
     }
+
+    fn queue_iter_with<F>(self, frontier: F) -> QueueIter<Self::IntoIter, F>
+    where
+        F: Frontier<Self::Item>,
+    {
+        queue_iter_with(self, frontier)
+    }
+
+    fn priority_queue_iter(self) -> PriorityQueueIter<Self::IntoIter>
+    where
+        Self::Item: Ord,
+    {
+        priority_queue_iter(self)
+    }
+
+    fn priority_queue_iter_by_key<F, K>(self, f: F) -> PriorityQueueIter<Self::IntoIter, ByKey<F>>
+    where
+        F: FnMut(&Self::Item) -> K,
+        K: Ord,
+    {
+        priority_queue_iter_by_key(self, f)
+    }
+
+    fn priority_queue_iter_by<C>(self, compare: C) -> PriorityQueueIter<Self::IntoIter, C>
+    where
+        C: Compare<Self::Item>,
+    {
+        priority_queue_iter_by(self, compare)
+    }
+
+    fn stack_iter(self) -> StackIter<Self::IntoIter> {
+        stack_iter(self)
+    }
+
+    fn expand<F, J>(self, f: F) -> Expand<Self::IntoIter, F>
+    where
+        F: FnMut(&Self::Item) -> J,
+        J: IntoIterator<Item = Self::Item>,
+    {
+        expand(self, f)
+    }
+
+    fn dedup_queue_iter(self) -> DedupQueueIter<Self::IntoIter, Self::Item, Identity<Self::Item>>
+    where
+        Self::Item: Clone + Hash + Eq,
+    {
+        dedup_queue_iter(self)
+    }
+
+    fn dedup_queue_iter_by_key<K, F>(self, key: F) -> DedupQueueIter<Self::IntoIter, K, F>
+    where
+        K: Hash + Eq,
+        F: FnMut(&Self::Item) -> K,
+    {
+        dedup_queue_iter_by_key(self, key)
+    }
+
+    fn depth_queue_iter(self) -> DepthQueueIter<Self::IntoIter> {
+        depth_queue_iter(self)
+    }
+
+    fn queue_iter_with_handle(self) -> (SharedQueueIter<Self::IntoIter>, Enqueuer<Self::Item>) {
+        queue_iter_with_handle(self)
+    }
+
+    fn sync_queue_iter(self) -> (SyncQueueIter<Self::IntoIter>, SyncEnqueuer<Self::Item>) {
+        sync_queue_iter(self)
+    }
 }
 
 /// Create an `Iterator` allowing for enqueuing elements to its end.
@@ -57,63 +356,1377 @@ pub fn queue_iter<I>(initial: I) -> QueueIter<I::IntoIter>
 where
     I: IntoIterator,
 {
-    QueueIter {
-        initial: initial.into_iter(),
-        next: VecDeque::default(),
-    }
+    queue_iter_with(initial, Fifo::default())
 }
 
-/// An `Iterator` allowing for enqueuing elements to its end. Elements can be added anytime, even
-/// after calling `next` has returned `None`, i.e. this `Iterator` can return `Some` after `None`.
-pub struct QueueIter<I>
+/// Create an `Iterator` allowing for enqueuing elements which are yielded in the order defined by
+/// the given [Frontier].
+///
+/// # Examples
+///
+/// ```
+/// use enqueue::{queue_iter_with, Lifo};
+///
+/// let i = std::iter::once(666);
+/// let mut i = queue_iter_with(i, Lifo::default());
+///
+/// i.enqueue(1);
+/// i.enqueue(42);
+/// assert_eq!(i.next(), Some(666));
+/// assert_eq!(i.next(), Some(42));
+/// assert_eq!(i.next(), Some(1));
+/// assert_eq!(i.next(), None);
+/// ```
+pub fn queue_iter_with<I, F>(initial: I, frontier: F) -> QueueIter<I::IntoIter, F>
 where
-    I: Iterator,
+    I: IntoIterator,
+    F: Frontier<I::Item>,
 {
-    initial: I,
-    next: VecDeque<I::Item>,
+    QueueIter {
+        initial: Some(initial.into_iter()),
+        front: VecDeque::default(),
+        next: frontier,
+        order: Order::default(),
+        side: Source::Initial,
+        taken: 0,
+        limit: None,
+        bytes: None,
+        dropped: 0,
+    }
+}
+
+/// Policy defining the order in which elements from the initial `Iterator` and enqueued elements
+/// of a [QueueIter] are yielded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Order {
+    /// Yield the initial elements first and only then the enqueued ones.
+    #[default]
+    InitialFirst,
+
+    /// Yield the enqueued elements first and only take from the initial `Iterator` if there are
+    /// no enqueued elements.
+    EnqueuedFirst,
+
+    /// Weighted round-robin: alternately yield up to `initial` initial elements and up to
+    /// `enqueued` enqueued elements. If one side has no elements, the other one is used.
+    Interleave { initial: usize, enqueued: usize },
+}
+
+/// Policy defining what happens when enqueuing to a [QueueIter] with a capacity limit, see
+/// [QueueIter::with_capacity_limit], or a byte budget, see [QueueIter::with_byte_limit], which is
+/// full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overflow {
+    /// Reject the new element: [QueueIter::try_enqueue] returns it back in a [Full] error,
+    /// [QueueIter::enqueue] drops it.
+    Reject,
+
+    /// Drop the earliest enqueued pending element to make room for the new element, regardless of
+    /// the [Frontier], see [Frontier::pop_oldest]. Elements buffered at the front by peeking or
+    /// putting back are only dropped if there are no other pending enqueued elements.
+    DropOldest,
+
+    /// Silently drop the new element.
+    DropNewest,
+
+    /// Panic.
+    Panic,
+}
+
+/// Error returned when enqueuing to a full queue, containing the rejected element.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Full<T>(pub T);
+
+impl<T> Display for Full<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "queue full")
+    }
+}
+
+impl<T> Error for Full<T> where T: Debug {}
+
+#[derive(Debug, Clone, Copy)]
+struct Limit {
+    capacity: usize,
+    overflow: Overflow,
+}
+
+/// The total [ByteSize] of the pending elements for a byte budget, see
+/// [QueueIter::with_byte_limit].
+struct Bytes<T, F> {
+    total: usize,
+
+    /// The length of the [Frontier] the total is up to date for, if any. Otherwise, or if the
+    /// frontier has been changed by others, the total is recounted.
+    len: Option<usize>,
+
+    size: fn(&T) -> usize,
+
+    /// The total size of the elements of the [Frontier].
+    sum: fn(&F) -> usize,
 }
 
-impl<I> QueueIter<I>
+impl<T, F> Bytes<T, F> {
+    fn add(&mut self, item: &T) {
+        self.total = self.total.saturating_add((self.size)(item));
+    }
+
+    fn remove(&mut self, item: &T) {
+        self.total = self.total.saturating_sub((self.size)(item));
+    }
+
+    fn pushed(&mut self) {
+        self.len = self.len.map(|len| len + 1);
+    }
+
+    fn popped(&mut self) {
+        self.len = self.len.and_then(|len| len.checked_sub(1));
+    }
+}
+
+impl<T, F> Clone for Bytes<T, F> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T, F> Copy for Bytes<T, F> {}
+
+/// The source of an element yielded by a [QueueIter].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Source {
+    /// The element was produced by the initial `Iterator`.
+    Initial,
+
+    /// The element was enqueued or put back to the front.
+    Enqueued,
+}
+
+impl Source {
+    fn other(self) -> Self {
+        match self {
+            Source::Initial => Source::Enqueued,
+            Source::Enqueued => Source::Initial,
+        }
+    }
+}
+
+/// An `Iterator` allowing for enqueuing elements. Enqueued elements are yielded after the initial
+/// ones in the order defined by the [Frontier], which defaults to [Fifo], i.e. to the end. Elements
+/// can be added anytime, even after calling `next` has returned `None`, i.e. this `Iterator` can
+/// return `Some` after `None`. Elements can also be put back to the front, e.g. for lexer-style
+/// pushback, see [QueueIter::push_front]. The initial `Iterator` is fused, i.e. not called anymore
+/// once it has returned `None`.
+pub struct QueueIter<I, F = Fifo<<I as Iterator>::Item>>
 where
     I: Iterator,
 {
-    /// Enqueue an element to the end of this `Iterator`.
-    pub fn enqueue(&mut self, item: I::Item) {
-        self.next.push_back(item)
-    }
+    initial: Option<I>,
+    front: VecDeque<(Source, I::Item)>,
+    next: F,
+    order: Order,
+    side: Source,
+    taken: usize,
+    limit: Option<Limit>,
+    bytes: Option<Bytes<I::Item, F>>,
+    dropped: u64,
 }
 
-impl<I> Iterator for QueueIter<I>
+impl<I, F> QueueIter<I, F>
 where
     I: Iterator,
+    F: Frontier<I::Item>,
 {
-    type Item = I::Item;
+    /// Use the given [Order] for yielding initial and enqueued elements.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the [Order] is `Interleave` with both weights being zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use enqueue::{IteratorExt, Order};
+    ///
+    /// let mut i = (0..4)
+    ///     .queue_iter()
+    ///     .with_order(Order::Interleave { initial: 2, enqueued: 1 });
+    ///
+    /// i.enqueue(42);
+    /// i.enqueue(43);
+    /// let items = i.collect::<Vec<_>>();
+    /// assert_eq!(items, [0, 1, 42, 2, 3, 43]);
+    /// ```
+    pub fn with_order(mut self, order: Order) -> Self {
+        if let Order::Interleave { initial, enqueued } = order {
+            assert!(
+                initial > 0 || enqueued > 0,
+                "interleave weights must not both be zero"
+            );
+        }
+        self.order = order;
+        self.side = Source::Initial;
+        self.taken = 0;
+        self
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        self.initial.next().or_else(|| self.next.pop_front())
+    /// Limit the number of pending elements, see [QueueIter::pending_len], to the given capacity,
+    /// applying the given [Overflow] policy when enqueuing to a full queue. Elements put back to
+    /// the front or enqueued via [Enqueuer] handles are not subject to the limit, i.e. always
+    /// accepted, but count towards the capacity.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacity is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use enqueue::{Full, IteratorExt, Overflow};
+    ///
+    /// let i = std::iter::once(666);
+    /// let mut i = i.queue_iter().with_capacity_limit(2, Overflow::Reject);
+    ///
+    /// i.enqueue(1);
+    /// i.enqueue(2);
+    /// assert_eq!(i.try_enqueue(3), Err(Full(3)));
+    /// i.enqueue(4);
+    /// assert_eq!(i.dropped(), 1);
+    /// assert_eq!(i.collect::<Vec<_>>(), [666, 1, 2]);
+    /// ```
+    pub fn with_capacity_limit(mut self, capacity: usize, overflow: Overflow) -> Self {
+        assert!(capacity > 0, "capacity must not be zero");
+        self.limit = Some(Limit { capacity, overflow });
+        self.bytes = None;
+        self
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Limit the total [ByteSize] of the pending elements, see [QueueIter::pending_len], to the
+    /// given budget, applying the given [Overflow] policy when enqueuing an element which does not
+    /// fit. An element exceeding the whole budget is only accepted if there are no other pending
+    /// elements. Like for [QueueIter::with_capacity_limit], elements put back to the front are
+    /// always accepted, but count towards the budget, as do elements enqueued before calling this
+    /// method.
+    ///
+    /// The total is kept up to date incrementally, but recounted via [InspectFrontier::iter] after
+    /// elements have been mutated or retained, see [QueueIter::pending_mut], [QueueIter::peek_mut]
+    /// and [QueueIter::retain]. As this requires an [InspectFrontier], [Shared] frontiers of
+    /// [Enqueuer] handles cannot have a byte budget.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the budget is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use enqueue::{IteratorExt, Overflow};
+    ///
+    /// let i = std::iter::empty();
+    /// let mut i = i.queue_iter().with_byte_limit(8, Overflow::DropOldest);
+    ///
+    /// i.enqueue(String::from("abc"));
+    /// i.enqueue(String::from("de"));
+    /// i.enqueue(String::from("fghi"));
+    /// assert_eq!(i.pending_bytes(), Some(6));
+    /// assert_eq!(i.dropped(), 1);
+    /// assert_eq!(i.collect::<Vec<_>>(), ["de", "fghi"]);
+    /// ```
+    pub fn with_byte_limit(mut self, budget: usize, overflow: Overflow) -> Self
+    where
+        I::Item: ByteSize,
+        F: InspectFrontier<I::Item>,
+    {
+        assert!(budget > 0, "budget must not be zero");
+        self.limit = Some(Limit {
+            capacity: budget,
+            overflow,
+        });
+        self.bytes = Some(Bytes {
+            total: 0,
+            len: None,
+            size: I::Item::byte_size,
+            sum: |next: &F| next.iter().map(I::Item::byte_size).sum(),
+        });
+        self
+    }
 
-    #[test]
-    fn test() {
-        let mut numbers = (0..10).queue_iter();
-        let mut sum = 0;
+    /// The total [ByteSize] of the pending elements if there is a byte budget, see
+    /// [QueueIter::with_byte_limit].
+    pub fn pending_bytes(&self) -> Option<usize> {
+        let bytes = self.bytes?;
+        if bytes.len == Some(self.next.len()) {
+            return Some(bytes.total);
+        }
+        let front = self.front_pending().map(bytes.size);
+        Some(front.fold((bytes.sum)(&self.next), usize::saturating_add))
+    }
 
-        while let Some(n) = numbers.next() {
-            sum += n;
-            if n < 5 {
-                numbers.enqueue(20);
+    /// Recount the total [ByteSize] of the pending elements if it is not up to date, see
+    /// [QueueIter::with_byte_limit].
+    fn recount_bytes(&mut self) {
+        if let Some(total) = self.pending_bytes() {
+            let len = self.next.len();
+            if let Some(bytes) = &mut self.bytes {
+                bytes.total = total;
+                bytes.len = Some(len);
             }
         }
+    }
 
-        // The sum of 0..10 is 45 and we enqueue 5 times 20, i.e. get another 100: 45 + 100.
-        assert_eq!(sum, 145);
+    /// The number of elements dropped because of the [Overflow] policy, see
+    /// [QueueIter::with_capacity_limit] and [QueueIter::with_byte_limit].
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
 
-        numbers.enqueue(42);
-        assert_eq!(numbers.next(), Some(42));
+    /// Enqueue an element, by default to the end of this `Iterator`. If the queue is full, see
+    /// [QueueIter::with_capacity_limit] and [QueueIter::with_byte_limit], the [Overflow] policy is
+    /// applied, rejected elements are dropped.
+    pub fn enqueue(&mut self, item: I::Item) {
+        if self.try_enqueue(item).is_err() {
+            self.dropped += 1;
+        }
+    }
+
+    /// Enqueue an element, by default to the end of this `Iterator`. If the queue is full, see
+    /// [QueueIter::with_capacity_limit] and [QueueIter::with_byte_limit], the [Overflow] policy is
+    /// applied and rejected elements are returned back in a [Full] error.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the queue is full and the [Overflow] policy is `Panic`.
+    pub fn try_enqueue(&mut self, item: I::Item) -> Result<(), Full<I::Item>> {
+        if let Some(limit) = self.limit {
+            self.recount_bytes();
+            let size = self.bytes.map_or(1, |bytes| (bytes.size)(&item));
+            if !self.make_room(limit, size) {
+                if limit.overflow == Overflow::DropNewest {
+                    self.dropped += 1;
+                    return Ok(());
+                }
+                return Err(Full(item));
+            }
+        }
+
+        if let Some(bytes) = &mut self.bytes {
+            bytes.add(&item);
+            bytes.pushed();
+        }
+        self.next.push(item);
+        Ok(())
+    }
+
+    /// Apply the [Overflow] policy if an element of the given size does not fit, returning whether
+    /// it fits now.
+    fn make_room(&mut self, limit: Limit, size: usize) -> bool {
+        if !self.is_full(limit, size) {
+            return true;
+        }
+        match limit.overflow {
+            Overflow::Reject | Overflow::DropNewest => false,
+
+            Overflow::DropOldest => {
+                // Bounded, because dropping an element of a lazily enqueued iterator may not
+                // shrink the frontier.
+                for _ in 0..self.pending_len() {
+                    if !self.drop_oldest() {
+                        break;
+                    }
+                    self.dropped += 1;
+                    if !self.is_full(limit, size) {
+                        return true;
+                    }
+                }
+                false
+            }
+
+            Overflow::Panic => panic!("queue capacity {} exceeded", limit.capacity),
+        }
+    }
+
+    /// Whether an element of the given size, which is one for a capacity limit, does not fit. An
+    /// element exceeding the whole budget fits if there are no other pending elements.
+    fn is_full(&self, limit: Limit, size: usize) -> bool {
+        let used = self.pending_bytes().unwrap_or_else(|| self.pending_len());
+        used > 0 && used.saturating_add(size) > limit.capacity
+    }
+
+    /// Drop the earliest enqueued pending element, see [Overflow::DropOldest], returning whether
+    /// there was one.
+    fn drop_oldest(&mut self) -> bool {
+        let item = match self.next.pop_oldest() {
+            Some(item) => {
+                if let Some(bytes) = &mut self.bytes {
+                    bytes.popped();
+                }
+                item
+            }
+            None => {
+                let Some(n) = self
+                    .front
+                    .iter()
+                    .position(|(source, _)| *source == Source::Enqueued)
+                else {
+                    return false;
+                };
+                self.next.front_removed(0);
+                self.front.remove(n).expect("element at position").1
+            }
+        };
+        if let Some(bytes) = &mut self.bytes {
+            bytes.remove(&item);
+        }
+        true
+    }
+
+    /// Put back an element to the front of this `Iterator`, i.e. it is yielded next, before any
+    /// pending enqueued element and before the remaining initial elements, regardless of the
+    /// [Frontier] and [Order]. If several elements are put back, the last one is yielded first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use enqueue::IteratorExt;
+    ///
+    /// let i = std::iter::once(666);
+    /// let mut i = i.queue_iter();
+    ///
+    /// i.enqueue(42);
+    /// i.push_front(1);
+    /// i.push_front(2);
+    /// assert_eq!(i.next(), Some(2));
+    /// assert_eq!(i.next(), Some(1));
+    /// assert_eq!(i.next(), Some(666));
+    /// assert_eq!(i.next(), Some(42));
+    /// assert_eq!(i.next(), None);
+    /// ```
+    pub fn push_front(&mut self, item: I::Item) {
+        if let Some(bytes) = &mut self.bytes {
+            bytes.add(&item);
+        }
+        self.next.front_inserted(0, &item);
+        self.front.push_front((Source::Enqueued, item))
+    }
+
+    /// Put back all the given elements to the front of this `Iterator` such that they are yielded
+    /// next in their original order, see [QueueIter::push_front].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use enqueue::IteratorExt;
+    ///
+    /// let i = std::iter::once(666);
+    /// let mut i = i.queue_iter();
+    ///
+    /// i.push_front(3);
+    /// i.push_front_all([1, 2]);
+    /// assert_eq!(i.next(), Some(1));
+    /// assert_eq!(i.next(), Some(2));
+    /// assert_eq!(i.next(), Some(3));
+    /// assert_eq!(i.next(), Some(666));
+    /// assert_eq!(i.next(), None);
+    /// ```
+    pub fn push_front_all<J>(&mut self, items: J)
+    where
+        J: IntoIterator<Item = I::Item>,
+    {
+        let items = items.into_iter().collect::<Vec<_>>();
+        for item in items.into_iter().rev() {
+            self.push_front(item);
+        }
+    }
+
+    /// Alias for [QueueIter::push_front].
+    pub fn unget(&mut self, item: I::Item) {
+        self.push_front(item)
+    }
+
+    /// The number of pending elements, i.e. elements which have been enqueued or put back, but not
+    /// yet yielded. This is a lower bound if the [Frontier] produces elements lazily, see
+    /// [Frontier::len].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use enqueue::IteratorExt;
+    ///
+    /// let i = std::iter::once(666);
+    /// let mut i = i.queue_iter();
+    ///
+    /// i.enqueue(42);
+    /// i.push_front(1);
+    /// assert_eq!(i.pending_len(), 2);
+    /// ```
+    pub fn pending_len(&self) -> usize {
+        self.front_pending().count() + self.next.len()
+    }
+
+    /// Remove all pending elements, see [QueueIter::pending_len].
+    pub fn clear_pending(&mut self) {
+        for _ in 0..self.front_pending().count() {
+            self.next.front_removed(0);
+        }
+        self.front.retain(|(source, _)| *source == Source::Initial);
+        self.next.clear();
+        if let Some(bytes) = &mut self.bytes {
+            bytes.total = 0;
+            bytes.len = Some(0);
+        }
+    }
+
+    /// Remove all pending elements, see [QueueIter::pending_len], and return them in the order they
+    /// would have been yielded. Notice that this pulls all lazily enqueued iterators, see
+    /// [QueueIter::enqueue_iter], hence it does not terminate for infinite ones; use
+    /// [QueueIter::clear_pending] to discard them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use enqueue::IteratorExt;
+    ///
+    /// let i = std::iter::once(666);
+    /// let mut i = i.queue_iter();
+    ///
+    /// i.enqueue(42);
+    /// i.push_front(1);
+    /// let pending = i.drain_pending().collect::<Vec<_>>();
+    /// assert_eq!(pending, [1, 42]);
+    /// assert_eq!(i.next(), Some(666));
+    /// assert_eq!(i.next(), None);
+    /// ```
+    pub fn drain_pending(&mut self) -> std::vec::IntoIter<I::Item> {
+        let (front, initial) = self
+            .front
+            .drain(..)
+            .partition::<Vec<_>, _>(|(source, _)| *source == Source::Enqueued);
+        self.front = initial.into();
+        for _ in 0..front.len() {
+            self.next.front_removed(0);
+        }
+
+        let mut pending = front.into_iter().map(|(_, item)| item).collect::<Vec<_>>();
+        pending.extend(std::iter::from_fn(|| self.next.pop()));
+        if let Some(bytes) = &mut self.bytes {
+            bytes.total = 0;
+            bytes.len = Some(0);
+        }
+        pending.into_iter()
+    }
+
+    fn front_pending(&self) -> impl Iterator<Item = &I::Item> {
+        self.front
+            .iter()
+            .filter(|(source, _)| *source == Source::Enqueued)
+            .map(|(_, item)| item)
+    }
+
+    /// Like `next`, but also returning the [Source] of the element.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use enqueue::{IteratorExt, Source};
+    ///
+    /// let i = std::iter::once(666);
+    /// let mut i = i.queue_iter();
+    ///
+    /// i.enqueue(42);
+    /// assert_eq!(i.next_with_source(), Some((Source::Initial, 666)));
+    /// assert_eq!(i.next_with_source(), Some((Source::Enqueued, 42)));
+    /// assert_eq!(i.next_with_source(), None);
+    /// ```
+    pub fn next_with_source(&mut self) -> Option<(Source, I::Item)> {
+        let (source, item) = match self.front.pop_front() {
+            Some((Source::Enqueued, item)) => {
+                self.next.front_removed(0);
+                (Source::Enqueued, item)
+            }
+            Some(entry) => entry,
+            None => self.take_next()?,
+        };
+        if let (Source::Enqueued, Some(bytes)) = (source, &mut self.bytes) {
+            bytes.remove(&item);
+        }
+        Some((source, item))
+    }
+
+    /// Return a reference to the element which is yielded next without consuming it.
+    ///
+    /// Peeked elements are buffered at the front, i.e. their order is fixed: elements enqueued
+    /// after peeking are yielded after the peeked ones, even if the [Frontier] or [Order] would
+    /// otherwise yield them earlier.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use enqueue::IteratorExt;
+    ///
+    /// let i = std::iter::once(666);
+    /// let mut i = i.queue_iter();
+    ///
+    /// assert_eq!(i.peek(), Some(&666));
+    /// i.enqueue(42);
+    /// assert_eq!(i.next(), Some(666));
+    /// assert_eq!(i.peek(), Some(&42));
+    /// assert_eq!(i.next(), Some(42));
+    /// assert_eq!(i.peek(), None);
+    /// ```
+    pub fn peek(&mut self) -> Option<&I::Item> {
+        self.peek_nth(0)
+    }
+
+    /// Return a mutable reference to the element which is yielded next without consuming it, see
+    /// [QueueIter::peek].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use enqueue::IteratorExt;
+    ///
+    /// let i = std::iter::once(666);
+    /// let mut i = i.queue_iter();
+    ///
+    /// if let Some(n) = i.peek_mut() {
+    ///     *n = 42;
+    /// }
+    /// assert_eq!(i.next(), Some(42));
+    /// ```
+    pub fn peek_mut(&mut self) -> Option<&mut I::Item> {
+        self.fill_front(0);
+        if let Some(bytes) = &mut self.bytes {
+            bytes.len = None;
+        }
+        self.front.front_mut().map(|(_, item)| item)
+    }
+
+    /// Return a reference to the `n`th (zero-based) element which is yielded next without
+    /// consuming any element, see [QueueIter::peek].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use enqueue::IteratorExt;
+    ///
+    /// let i = std::iter::once(666);
+    /// let mut i = i.queue_iter();
+    ///
+    /// i.enqueue(42);
+    /// assert_eq!(i.peek_nth(1), Some(&42));
+    /// assert_eq!(i.peek_nth(2), None);
+    /// assert_eq!(i.next(), Some(666));
+    /// assert_eq!(i.next(), Some(42));
+    /// ```
+    pub fn peek_nth(&mut self, n: usize) -> Option<&I::Item> {
+        self.fill_front(n);
+        self.front.get(n).map(|(_, item)| item)
+    }
+
+    /// Buffer elements at the front until there are `n + 1` or no more elements.
+    fn fill_front(&mut self, n: usize) {
+        while self.front.len() <= n {
+            match self.take_next() {
+                Some((Source::Enqueued, item)) => {
+                    let n = self.front_pending().count();
+                    self.next.front_inserted(n, &item);
+                    self.front.push_back((Source::Enqueued, item));
+                }
+                Some(entry) => self.front.push_back(entry),
+                None => break,
+            }
+        }
+    }
+
+    fn take_next(&mut self) -> Option<(Source, I::Item)> {
+        match self.order {
+            Order::InitialFirst => self
+                .take(Source::Initial)
+                .or_else(|| self.take(Source::Enqueued)),
+            Order::EnqueuedFirst => self
+                .take(Source::Enqueued)
+                .or_else(|| self.take(Source::Initial)),
+            Order::Interleave { initial, enqueued } => self.next_interleaved(initial, enqueued),
+        }
+    }
+
+    /// Whether the initial `Iterator` has been exhausted, i.e. has returned `None`. Notice that
+    /// this only becomes `true` once `next` has tried to take a further initial element.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use enqueue::IteratorExt;
+    ///
+    /// let i = std::iter::once(666);
+    /// let mut i = i.queue_iter();
+    ///
+    /// assert_eq!(i.next(), Some(666));
+    /// assert!(!i.is_initial_exhausted());
+    /// assert_eq!(i.next(), None);
+    /// assert!(i.is_initial_exhausted());
+    /// ```
+    pub fn is_initial_exhausted(&self) -> bool {
+        self.initial.is_none()
+    }
+
+    fn take(&mut self, source: Source) -> Option<(Source, I::Item)> {
+        let item = match source {
+            Source::Initial => {
+                let item = self.initial.as_mut().and_then(Iterator::next);
+                if item.is_none() {
+                    self.initial = None;
+                }
+                item
+            }
+            Source::Enqueued => {
+                let item = self.next.pop();
+                if let (Some(_), Some(bytes)) = (&item, &mut self.bytes) {
+                    bytes.popped();
+                }
+                item
+            }
+        };
+        item.map(|item| (source, item))
+    }
+
+    fn next_interleaved(&mut self, initial: usize, enqueued: usize) -> Option<(Source, I::Item)> {
+        let weight = |side| match side {
+            Source::Initial => initial,
+            Source::Enqueued => enqueued,
+        };
+        while self.taken >= weight(self.side) {
+            self.side = self.side.other();
+            self.taken = 0;
+        }
+
+        match self.take(self.side) {
+            Some(item) => {
+                self.taken += 1;
+                Some(item)
+            }
+
+            // The current side has no elements, hence switch to the other one.
+            None => {
+                let item = self.take(self.side.other());
+                if item.is_some() {
+                    self.side = self.side.other();
+                    self.taken = 1;
+                }
+                item
+            }
+        }
+    }
+}
+
+impl<I, F> QueueIter<I, F>
+where
+    I: Iterator,
+    F: InspectFrontier<I::Item>,
+{
+    /// Iterate over the pending elements, see [QueueIter::pending_len]: first the ones put back,
+    /// then the enqueued ones in the order given by [InspectFrontier::iter].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use enqueue::IteratorExt;
+    ///
+    /// let i = std::iter::once(666);
+    /// let mut i = i.queue_iter();
+    ///
+    /// i.enqueue(42);
+    /// i.push_front(1);
+    /// let pending = i.pending().copied().collect::<Vec<_>>();
+    /// assert_eq!(pending, [1, 42]);
+    /// ```
+    pub fn pending(&self) -> impl Iterator<Item = &I::Item> {
+        self.front_pending().chain(self.next.iter())
+    }
+
+    /// Only retain the pending elements, see [QueueIter::pending_len], for which the given
+    /// predicate returns `true`.
+    pub fn retain<P>(&mut self, mut f: P)
+    where
+        P: FnMut(&I::Item) -> bool,
+    {
+        let next = &mut self.next;
+        let mut n = 0;
+        self.front.retain(|(source, item)| {
+            if *source == Source::Initial {
+                true
+            } else if f(item) {
+                n += 1;
+                true
+            } else {
+                next.front_removed(n);
+                false
+            }
+        });
+        self.next.retain(f);
+        if let Some(bytes) = &mut self.bytes {
+            bytes.len = None;
+        }
+    }
+
+    /// Remove the pending elements, see [QueueIter::pending_len], for which the given predicate
+    /// returns `true` and return the number of removed elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use enqueue::IteratorExt;
+    ///
+    /// let mut i = (0..2).queue_iter();
+    ///
+    /// i.enqueue(1);
+    /// i.enqueue(2);
+    /// i.enqueue(3);
+    /// assert_eq!(i.remove_where(|n| n % 2 == 1), 2);
+    /// assert_eq!(i.collect::<Vec<_>>(), [0, 1, 2]);
+    /// ```
+    pub fn remove_where<P>(&mut self, mut f: P) -> usize
+    where
+        P: FnMut(&I::Item) -> bool,
+    {
+        let len = self.pending_len();
+        self.retain(|item| !f(item));
+        len - self.pending_len()
+    }
+}
+
+impl<I, F> QueueIter<I, F>
+where
+    I: Iterator,
+    F: InspectFrontierMut<I::Item>,
+{
+    /// Iterate over mutable references to the pending elements, see [QueueIter::pending].
+    pub fn pending_mut(&mut self) -> impl Iterator<Item = &mut I::Item> {
+        if let Some(bytes) = &mut self.bytes {
+            bytes.len = None;
+        }
+        self.front
+            .iter_mut()
+            .filter(|(source, _)| *source == Source::Enqueued)
+            .map(|(_, item)| item)
+            .chain(self.next.iter_mut())
+    }
+}
+
+impl<'a, I> QueueIter<I, LazyFifo<'a, I::Item>>
+where
+    I: Iterator,
+    I::Item: 'a,
+{
+    /// Enqueue all elements of the given iterator to the end of this `Iterator`, without
+    /// materializing them: the iterator itself is enqueued and only pulled from when its turn
+    /// comes.
+    ///
+    /// If there is a capacity limit, see [QueueIter::with_capacity_limit], the iterator counts
+    /// towards it by the lower bound of its length, but at least one, and the [Overflow] policy is
+    /// applied to it as a whole; a rejected iterator counts as one dropped element. An iterator
+    /// whose lower bound exceeds the capacity, e.g. an infinite one, is always rejected. With
+    /// `DropOldest`, the new element is rejected if no room can be made by dropping as many
+    /// elements as were pending, e.g. because a lazily enqueued iterator has no exact length.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use enqueue::{IteratorExt, LazyFifo};
+    ///
+    /// let i = std::iter::once(666);
+    /// let mut i = i.queue_iter_with(LazyFifo::default());
+    ///
+    /// i.enqueue_iter(0..u64::MAX);
+    /// i.enqueue(42);
+    /// assert_eq!(i.next(), Some(666));
+    /// assert_eq!(i.next(), Some(0));
+    /// assert_eq!(i.next(), Some(1));
+    /// ```
+    pub fn enqueue_iter<J>(&mut self, items: J)
+    where
+        J: IntoIterator<Item = I::Item>,
+        J::IntoIter: 'a,
+    {
+        let items = items.into_iter();
+        if let Some(limit) = self.limit {
+            let size = items.size_hint().0.max(1);
+            if size > limit.capacity {
+                if limit.overflow == Overflow::Panic {
+                    panic!("queue capacity {} exceeded", limit.capacity);
+                }
+                self.dropped += 1;
+                return;
+            }
+            if !self.make_room(limit, size) {
+                self.dropped += 1;
+                return;
+            }
+        }
+        self.next.push_iter(items);
+    }
+}
+
+impl<I, F> Iterator for QueueIter<I, F>
+where
+    I: Iterator,
+    F: Frontier<I::Item>,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_with_source().map(|(_, item)| item)
+    }
+
+    /// The lower bound accounts for the remaining initial and the pending elements, there is no
+    /// upper bound, because further elements may be enqueued anytime.
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let initial = self
+            .initial
+            .as_ref()
+            .map_or(0, |initial| initial.size_hint().0);
+        let lower = initial
+            .saturating_add(self.front.len())
+            .saturating_add(self.next.len());
+        (lower, None)
+    }
+}
+
+impl<I, F> Extend<I::Item> for QueueIter<I, F>
+where
+    I: Iterator,
+    F: Frontier<I::Item>,
+{
+    /// Enqueue all the given elements.
+    fn extend<J>(&mut self, items: J)
+    where
+        J: IntoIterator<Item = I::Item>,
+    {
+        for item in items {
+            self.enqueue(item);
+        }
+    }
+}
+
+impl<I, F> Debug for QueueIter<I, F>
+where
+    I: Iterator + Debug,
+    I::Item: Debug,
+    F: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("QueueIter")
+            .field("initial", &self.initial)
+            .field("front", &self.front)
+            .field("next", &self.next)
+            .field("order", &self.order)
+            .field("limit", &self.limit)
+            .finish()
+    }
+}
+
+impl<I, F> Clone for QueueIter<I, F>
+where
+    I: Iterator + Clone,
+    I::Item: Clone,
+    F: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            initial: self.initial.clone(),
+            front: self.front.clone(),
+            next: self.next.clone(),
+            order: self.order,
+            side: self.side,
+            taken: self.taken,
+            limit: self.limit,
+            bytes: self.bytes,
+            dropped: self.dropped,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test() {
+        let mut numbers = (0..10).queue_iter();
+        let mut sum = 0;
+
+        while let Some(n) = numbers.next() {
+            sum += n;
+            if n < 5 {
+                numbers.enqueue(20);
+            }
+        }
+
+        // The sum of 0..10 is 45 and we enqueue 5 times 20, i.e. get another 100: 45 + 100.
+        assert_eq!(sum, 145);
+
+        numbers.enqueue(42);
+        assert_eq!(numbers.next(), Some(42));
+    }
+
+    #[test]
+    fn test_frontier() {
+        fn traverse<F>() -> Vec<u32>
+        where
+            F: Frontier<u32> + Default,
+        {
+            let mut nodes = queue_iter_with([1], F::default());
+            let mut visited = vec![];
+            while let Some(n) = nodes.next() {
+                visited.push(n);
+                if n < 4 {
+                    nodes.enqueue(2 * n);
+                    nodes.enqueue(2 * n + 1);
+                }
+            }
+            visited
+        }
+
+        assert_eq!(traverse::<Fifo<_>>(), [1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!(traverse::<Lifo<_>>(), [1, 3, 7, 6, 2, 5, 4]);
+        assert_eq!(traverse::<Priority<_>>(), [1, 3, 7, 6, 2, 5, 4]);
+
+        let mut visited = traverse::<Random<_>>();
+        visited.sort();
+        assert_eq!(visited, [1, 2, 3, 4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn test_order() {
+        let mut numbers = (0..3).queue_iter().with_order(Order::EnqueuedFirst);
+        let mut visited = vec![];
+
+        while let Some(n) = numbers.next() {
+            visited.push(n);
+            if n < 2 {
+                numbers.enqueue(n + 10);
+            }
+        }
+        assert_eq!(visited, [0, 10, 1, 11, 2]);
+
+        let mut numbers = (0..5).queue_iter().with_order(Order::Interleave {
+            initial: 1,
+            enqueued: 2,
+        });
+        for n in 10..14 {
+            numbers.enqueue(n);
+        }
+        let numbers = numbers.collect::<Vec<_>>();
+        assert_eq!(numbers, [0, 10, 11, 1, 12, 13, 2, 3, 4]);
+
+        let mut numbers = (0..2).queue_iter().with_order(Order::Interleave {
+            initial: 0,
+            enqueued: 1,
+        });
+        numbers.enqueue(10);
+        let numbers = numbers.collect::<Vec<_>>();
+        assert_eq!(numbers, [10, 0, 1]);
+    }
+
+    #[test]
+    fn test_fused() {
+        // Not fused: alternately yields `Some` and `None`.
+        let mut n = 0;
+        let initial = std::iter::from_fn(move || {
+            n += 1;
+            (n % 2 == 0).then_some(n)
+        });
+        let mut numbers = initial.queue_iter();
+
+        assert_eq!(numbers.next(), None);
+        assert!(numbers.is_initial_exhausted());
+        assert_eq!(numbers.next(), None);
+
+        numbers.enqueue(42);
+        assert_eq!(numbers.next_with_source(), Some((Source::Enqueued, 42)));
+        assert_eq!(numbers.next(), None);
+    }
+
+    #[test]
+    fn test_push_front() {
+        // Lexer-style pushback: split "ab" tokens into "a" and "b" by ungetting "b".
+        let mut tokens = ["ab", "c"].queue_iter();
+        tokens.enqueue("d");
+        let mut lexed = vec![];
+
+        while let Some(token) = tokens.next() {
+            if token.len() > 1 {
+                let (head, tail) = token.split_at(1);
+                tokens.unget(tail);
+                lexed.push(head);
+            } else {
+                lexed.push(token);
+            }
+        }
+        assert_eq!(lexed, ["a", "b", "c", "d"]);
+
+        let mut numbers = (0..2)
+            .queue_iter_with(Lifo::default())
+            .with_order(Order::EnqueuedFirst);
+        numbers.enqueue(10);
+        numbers.enqueue(11);
+        numbers.push_front_all([20, 21]);
+        numbers.push_front(22);
+        let numbers = numbers.collect::<Vec<_>>();
+        assert_eq!(numbers, [22, 20, 21, 11, 10, 0, 1]);
+    }
+
+    #[test]
+    fn test_peek() {
+        let mut numbers = (0..2).queue_iter().with_order(Order::EnqueuedFirst);
+
+        assert_eq!(numbers.peek_nth(1), Some(&1));
+        numbers.enqueue(10);
+        numbers.push_front(20);
+        assert_eq!(numbers.peek(), Some(&20));
+        assert_eq!(numbers.peek_nth(3), Some(&10));
+        assert_eq!(numbers.peek_nth(4), None);
+        assert!(numbers.is_initial_exhausted());
+
+        let numbers = numbers.collect::<Vec<_>>();
+        assert_eq!(numbers, [20, 0, 1, 10]);
+    }
+
+    #[test]
+    fn test_pending() {
+        let mut numbers = (0..3).queue_iter();
+        for n in 10..15 {
+            numbers.enqueue(n);
+        }
+        numbers.push_front(20);
+        assert_eq!(numbers.peek_nth(2), Some(&1));
+        assert_eq!(numbers.pending_len(), 6);
+
+        numbers.retain(|n| n % 2 == 0);
+        for n in numbers.pending_mut() {
+            *n += 100;
+        }
+        let pending = numbers.pending().copied().collect::<Vec<_>>();
+        assert_eq!(pending, [120, 110, 112, 114]);
+
+        let drained = numbers.drain_pending().collect::<Vec<_>>();
+        assert_eq!(drained, [120, 110, 112, 114]);
+        assert_eq!(numbers.pending_len(), 0);
+
+        numbers.enqueue(42);
+        numbers.clear_pending();
+        assert_eq!(numbers.pending_len(), 0);
+        assert_eq!(numbers.collect::<Vec<_>>(), [0, 1, 2]);
+    }
+
+    #[test]
+    fn test_std_traits() {
+        let mut numbers = (0..3).queue_iter();
+        numbers.extend([10, 11]);
+        numbers.push_front(20);
+        assert_eq!(numbers.size_hint(), (6, None));
+
+        let cloned = numbers.clone();
+        assert_eq!(
+            format!("{cloned:?}"),
+            "QueueIter { initial: Some(0..3), front: [(Enqueued, 20)], next: Fifo([10, 11]), \
+             order: InitialFirst, limit: None }"
+        );
+
+        let numbers = numbers.collect::<Vec<_>>();
+        assert!(numbers.capacity() >= 6);
+        assert_eq!(numbers, cloned.collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_enqueue_iter() {
+        // Breadth-first traversal of an infinite tree, node n having children 10n + 1, 10n + 2, ...
+        let mut nodes = [0_u64].queue_iter_with(LazyFifo::default());
+        let mut visited = vec![];
+
+        while let Some(n) = nodes.next() {
+            visited.push(n);
+            if visited.len() == 5 {
+                break;
+            }
+            nodes.enqueue_iter((1..).map(move |c| 10 * n + c));
+        }
+
+        assert_eq!(visited, [0, 1, 2, 3, 4]);
+        // The length of an infinite iterator saturates.
+        assert_eq!(nodes.pending_len(), usize::MAX);
+
+        // Clearing drops the infinite iterators without pulling from them.
+        nodes.clear_pending();
+        assert_eq!(nodes.pending_len(), 0);
+        assert_eq!(nodes.next(), None);
+
+        // The length is a lower bound for iterators without an exact size.
+        nodes.enqueue_iter((0..10).filter(|n| n % 2 == 0));
+        assert_eq!(nodes.pending_len(), 1);
+        assert_eq!(nodes.count(), 5);
+    }
+
+    #[test]
+    fn test_capacity_limit() {
+        let mut numbers = None
+            .queue_iter()
+            .with_capacity_limit(2, Overflow::DropOldest);
+        numbers.extend(0..5);
+        numbers.push_front(10);
+        assert_eq!(numbers.dropped(), 3);
+        assert_eq!(numbers.collect::<Vec<_>>(), [10, 3, 4]);
+
+        let mut numbers = None
+            .queue_iter()
+            .with_capacity_limit(2, Overflow::DropNewest);
+        numbers.extend(0..5);
+        assert_eq!(numbers.try_enqueue(5), Ok(()));
+        assert_eq!(numbers.dropped(), 4);
+        assert_eq!(numbers.collect::<Vec<_>>(), [0, 1]);
+
+        let mut numbers = None
+            .queue_iter_with(LazyFifo::default())
+            .with_capacity_limit(2, Overflow::Reject);
+        numbers.enqueue_iter(0..3);
+        assert_eq!(numbers.dropped(), 1);
+        numbers.enqueue_iter(0..2);
+        assert_eq!(numbers.try_enqueue(5), Err(Full(5)));
+        assert_eq!(numbers.next(), Some(0));
+        assert_eq!(numbers.try_enqueue(5), Ok(()));
+        assert_eq!(numbers.collect::<Vec<_>>(), [1, 5]);
+
+        // Lazily enqueued iterators are neither pulled from eagerly nor dropped from endlessly.
+        let mut numbers = None
+            .queue_iter_with(LazyFifo::default())
+            .with_capacity_limit(2, Overflow::DropOldest);
+        numbers.enqueue_iter(0u64..);
+        assert_eq!(numbers.dropped(), 1);
+        assert_eq!(numbers.pending_len(), 0);
+        numbers.enqueue_iter((0u64..).filter(|n| n % 2 == 0));
+        numbers.enqueue(1);
+        assert_eq!(numbers.try_enqueue(3), Err(Full(3)));
+        assert_eq!(numbers.dropped(), 3);
+        assert_eq!(numbers.next(), Some(4));
+
+        // Peeked elements count towards the capacity.
+        let mut numbers = None.queue_iter().with_capacity_limit(2, Overflow::Reject);
+        numbers.extend(0..2);
+        assert_eq!(numbers.peek_nth(1), Some(&1));
+        assert_eq!(numbers.try_enqueue(2), Err(Full(2)));
+        assert_eq!(numbers.pending_len(), 2);
+
+        // Peeked elements are only dropped if there are no others.
+        let mut numbers = None
+            .queue_iter()
+            .with_capacity_limit(2, Overflow::DropOldest);
+        numbers.extend(0..2);
+        assert_eq!(numbers.peek(), Some(&0));
+        numbers.enqueue(2);
+        assert_eq!(numbers.peek_nth(1), Some(&2));
+        numbers.enqueue(3);
+        assert_eq!(numbers.collect::<Vec<_>>(), [2, 3]);
+
+        // The earliest enqueued element is dropped regardless of the frontier.
+        let mut numbers = priority_queue_iter(None).with_capacity_limit(2, Overflow::DropOldest);
+        numbers.extend([50, 100, 1]);
+        assert_eq!(numbers.collect::<Vec<_>>(), [100, 1]);
+
+        let mut numbers = stack_iter(None).with_capacity_limit(2, Overflow::DropOldest);
+        numbers.extend([1, 2, 3]);
+        assert_eq!(numbers.collect::<Vec<_>>(), [3, 2]);
+    }
+
+    #[test]
+    fn test_byte_limit() {
+        let bytes = |n| vec![0u8; n];
+
+        let mut items = None.queue_iter().with_byte_limit(10, Overflow::Reject);
+        items.enqueue(bytes(4));
+        items.enqueue(bytes(4));
+        assert_eq!(items.try_enqueue(bytes(4)), Err(Full(bytes(4))));
+        items.enqueue(bytes(2));
+        assert_eq!(items.pending_bytes(), Some(10));
+        assert_eq!(items.next(), Some(bytes(4)));
+        assert_eq!(items.pending_bytes(), Some(6));
+        items.retain(|item| item.len() != 4);
+        assert_eq!(items.pending_bytes(), Some(2));
+        items.clear_pending();
+        assert_eq!(items.pending_bytes(), Some(0));
+
+        // An element exceeding the whole budget is only accepted if there are no others.
+        items.enqueue(bytes(20));
+        assert_eq!(items.try_enqueue(bytes(1)), Err(Full(bytes(1))));
+        assert_eq!(items.next(), Some(bytes(20)));
+        assert_eq!(items.pending_bytes(), Some(0));
+
+        // Elements enqueued before setting the limit or put back count towards the budget.
+        let mut items = None.queue_iter();
+        items.enqueue(bytes(6));
+        let mut items = items.with_byte_limit(10, Overflow::Reject);
+        assert_eq!(items.pending_bytes(), Some(6));
+        assert_eq!(items.try_enqueue(bytes(6)), Err(Full(bytes(6))));
+        items.push_front(bytes(3));
+        assert_eq!(items.pending_bytes(), Some(9));
+        assert_eq!(items.try_enqueue(bytes(2)), Err(Full(bytes(2))));
+        assert_eq!(items.next(), Some(bytes(3)));
+        items.enqueue(bytes(4));
+        assert_eq!(items.next(), Some(bytes(6)));
+        assert_eq!(items.pending_bytes(), Some(4));
+
+        // Mutated elements are recounted.
+        for item in items.pending_mut() {
+            item.push(0);
+        }
+        assert_eq!(items.pending_bytes(), Some(5));
+        if let Some(item) = items.peek_mut() {
+            item.clear();
+        }
+        assert_eq!(items.pending_bytes(), Some(0));
+
+        // The budget is never exceeded, whatever order elements are yielded in.
+        let mut items = None
+            .queue_iter_with(Random::with_seed(42))
+            .with_byte_limit(100, Overflow::DropOldest);
+        for n in 0..1000 {
+            items.enqueue(bytes(n * 7 % 30));
+            if n % 3 == 0 {
+                items.next();
+            }
+            if n % 5 == 0 {
+                items.peek_nth(2);
+            }
+            let total = items.pending().map(Vec::len).sum::<usize>();
+            assert_eq!(items.pending_bytes(), Some(total));
+            assert!(total <= 100);
+        }
+
+        let mut items = None.queue_iter().with_byte_limit(10, Overflow::DropOldest);
+        for n in 1..=5 {
+            items.enqueue(bytes(n));
+        }
+        assert_eq!(items.pending_bytes(), Some(9));
+        assert_eq!(items.dropped(), 3);
+        let items = items.map(|item| item.len()).collect::<Vec<_>>();
+        assert_eq!(items, [4, 5]);
+
+        let numbers = (0..3).queue_iter().with_capacity_limit(2, Overflow::Reject);
+        assert_eq!(numbers.pending_bytes(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "queue capacity 2 exceeded")]
+    fn test_capacity_limit_panic_iter() {
+        let mut numbers = None
+            .queue_iter_with(LazyFifo::default())
+            .with_capacity_limit(2, Overflow::Panic);
+        numbers.enqueue_iter(0..3);
+    }
+
+    #[test]
+    #[should_panic(expected = "queue capacity 1 exceeded")]
+    fn test_capacity_limit_panic() {
+        let mut numbers = None.queue_iter().with_capacity_limit(1, Overflow::Panic);
+        numbers.extend(0..2);
     }
 }
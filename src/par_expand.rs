@@ -0,0 +1,166 @@
+use rayon::iter::{
+    plumbing::{Folder, Reducer, UnindexedConsumer},
+    ParallelIterator,
+};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Create a rayon `ParallelIterator` yielding the given seed elements and, recursively, the
+/// children returned by the given function for each yielded element, i.e. the parallel
+/// counterpart of [expand](crate::expand). Elements are yielded in no particular order.
+///
+/// # Examples
+///
+/// ```
+/// use enqueue::par_expand;
+/// use rayon::iter::ParallelIterator;
+///
+/// let i = par_expand([1_u64], |&n| if n < 4 { vec![2 * n, 2 * n + 1] } else { vec![] });
+///
+/// let sum = i.filter(|n| n % 2 == 1).sum::<u64>();
+/// assert_eq!(sum, 16);
+/// ```
+pub fn par_expand<S, F, I>(seeds: S, children: F) -> ParExpand<S::Item, F>
+where
+    S: IntoIterator,
+    S::Item: Send,
+    F: Fn(&S::Item) -> I + Send + Sync,
+    I: IntoIterator<Item = S::Item>,
+{
+    ParExpand {
+        seeds: seeds.into_iter().collect(),
+        children,
+    }
+}
+
+/// A rayon `ParallelIterator` yielding seed elements and, recursively, their children, see
+/// [par_expand].
+#[derive(Debug, Clone)]
+pub struct ParExpand<T, F> {
+    seeds: Vec<T>,
+    children: F,
+}
+
+impl<T, F, I> ParallelIterator for ParExpand<T, F>
+where
+    T: Send,
+    F: Fn(&T) -> I + Send + Sync,
+    I: IntoIterator<Item = T>,
+{
+    type Item = T;
+
+    fn drive_unindexed<C>(self, consumer: C) -> C::Result
+    where
+        C: UnindexedConsumer<Self::Item>,
+    {
+        drive(self.seeds, &self.children, consumer, None)
+    }
+}
+
+/// Maximum number of elements yielded sequentially before checking whether to split again.
+const CHUNK_LEN: usize = 1024;
+
+/// Yield the given elements and their descendants to the given consumer.
+///
+/// Half of the elements is offered to other threads initially and again whenever the previous
+/// offer, signalled by `stolen`, has been taken by another thread. Otherwise a bounded chunk is
+/// expanded sequentially before checking again, so that long chains of single children neither
+/// block splitting nor have to be expanded completely first.
+fn drive<T, F, I, C>(
+    mut items: Vec<T>,
+    children: &F,
+    consumer: C,
+    stolen: Option<&AtomicBool>,
+) -> C::Result
+where
+    T: Send,
+    F: Fn(&T) -> I + Sync,
+    I: IntoIterator<Item = T>,
+    C: UnindexedConsumer<T>,
+{
+    let mut result: Option<C::Result> = None;
+    while !items.is_empty() && !consumer.full() {
+        if items.len() >= 2 && stolen.map_or(true, |stolen| stolen.load(Ordering::Relaxed)) {
+            let other = items.split_off(items.len() / 2);
+            let left = consumer.split_off_left();
+            let (reducer, outer) = (consumer.to_reducer(), consumer.to_reducer());
+            let taken = AtomicBool::new(false);
+            let (left, right) = rayon::join_context(
+                |_| drive(items, children, left, Some(&taken)),
+                |context| {
+                    taken.store(context.migrated(), Ordering::Relaxed);
+                    drive(other, children, consumer, None)
+                },
+            );
+            let joined = reducer.reduce(left, right);
+            return match result {
+                Some(result) => outer.reduce(result, joined),
+                None => joined,
+            };
+        }
+
+        // Sequentially expand depth-first to keep the stack small.
+        let mut folder = consumer.split_off_left().into_folder();
+        for _ in 0..CHUNK_LEN {
+            if folder.full() {
+                break;
+            }
+            let Some(item) = items.pop() else { break };
+            items.extend(children(&item));
+            folder = folder.consume(item);
+        }
+        let chunk = folder.complete();
+        result = Some(match result {
+            Some(result) => consumer.to_reducer().reduce(result, chunk),
+            None => chunk,
+        });
+    }
+
+    let rest = consumer.to_reducer();
+    let last = consumer.into_folder().complete();
+    match result {
+        Some(result) => rest.reduce(result, last),
+        None => last,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test() {
+        // Complete binary tree of depth 16, node n having children 2n and 2n + 1.
+        let nodes = par_expand([1_u64], |&n| {
+            (n < 1 << 16)
+                .then_some([2 * n, 2 * n + 1])
+                .into_iter()
+                .flatten()
+        });
+
+        let mut nodes = nodes.collect::<Vec<_>>();
+        nodes.sort();
+        assert_eq!(nodes, (1..1 << 17).collect::<Vec<_>>());
+
+        let none = par_expand(Vec::<u64>::new(), |_| None).count();
+        assert_eq!(none, 0);
+    }
+
+    #[test]
+    fn test_single_child() {
+        // Chain 0 -> 1 -> ... -> 4096 followed by a binary tree below 4096.
+        let children = |&n: &u64| match n {
+            n if n < 1 << 12 => vec![n + 1],
+            n if n < 1 << 14 => vec![2 * n, 2 * n + 1],
+            _ => vec![],
+        };
+        let mut nodes = par_expand([0_u64], children).collect::<Vec<_>>();
+        nodes.sort();
+        let mut expected = crate::expand([0_u64], children).collect::<Vec<_>>();
+        expected.sort();
+        assert_eq!(nodes, expected);
+
+        // Infinite chain.
+        let found = par_expand([0_u64], |&n| Some(n + 1)).find_any(|&n| n == 10);
+        assert_eq!(found, Some(10));
+    }
+}
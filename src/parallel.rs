@@ -0,0 +1,219 @@
+use crossbeam_deque::{Injector, Steal, Stealer, Worker};
+use crossbeam_utils::Backoff;
+use std::{
+    fmt::{self, Debug, Formatter},
+    iter,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Condvar, Mutex, PoisonError,
+    },
+    thread,
+};
+
+/// Process the given seed elements and, recursively, all elements enqueued while processing, with
+/// the given function on `threads` threads. Each thread has its own deque and steals from the
+/// others when it runs out of elements; if there are none to steal, it backs off and eventually
+/// sleeps until further elements are enqueued. Returns once all elements have been processed.
+///
+/// # Panics
+///
+/// Panics if `threads` is zero or if the given function panics; in the latter case the remaining
+/// elements are processed by the other threads before panicking.
+///
+/// # Examples
+///
+/// ```
+/// use enqueue::run_parallel;
+/// use std::sync::atomic::{AtomicU64, Ordering};
+///
+/// let sum = AtomicU64::new(0);
+///
+/// run_parallel([1], 4, |n, enqueuer| {
+///     sum.fetch_add(n, Ordering::Relaxed);
+///     if n < 4 {
+///         enqueuer.enqueue(2 * n);
+///         enqueuer.enqueue(2 * n + 1);
+///     }
+/// });
+///
+/// assert_eq!(sum.into_inner(), 28);
+/// ```
+pub fn run_parallel<I, F>(seeds: I, threads: usize, f: F)
+where
+    I: IntoIterator,
+    I::Item: Send,
+    F: Fn(I::Item, &ParallelEnqueuer<'_, I::Item>) + Sync,
+{
+    assert!(threads > 0, "threads must not be zero");
+
+    let injector = Injector::new();
+    let state = State::default();
+    for seed in seeds {
+        state.pending.fetch_add(1, Ordering::SeqCst);
+        injector.push(seed);
+    }
+
+    let workers = iter::repeat_with(Worker::new_fifo)
+        .take(threads)
+        .collect::<Vec<_>>();
+    let stealers = workers.iter().map(Worker::stealer).collect::<Vec<_>>();
+    let (injector, stealers, state, f) = (&injector, &stealers, &state, &f);
+
+    thread::scope(|scope| {
+        for worker in workers {
+            scope.spawn(move || {
+                let enqueuer = ParallelEnqueuer { worker, state };
+                let backoff = Backoff::new();
+                loop {
+                    // Read the epoch before looking for an element to not miss a notification.
+                    let epoch = state.epoch.load(Ordering::SeqCst);
+                    match find_item(&enqueuer.worker, injector, stealers) {
+                        Some(item) => {
+                            backoff.reset();
+                            // Decrement the pending count even if `f` panics.
+                            let _done = Done(state);
+                            f(item, &enqueuer);
+                        }
+                        None if state.pending.load(Ordering::SeqCst) == 0 => break,
+                        None if backoff.is_completed() => {
+                            state.sleep(epoch);
+                            backoff.reset();
+                        }
+                        None => backoff.snooze(),
+                    }
+                }
+            });
+        }
+    });
+}
+
+/// Handle for enqueuing elements to the deque of the current thread of [run_parallel].
+pub struct ParallelEnqueuer<'a, T> {
+    worker: Worker<T>,
+    state: &'a State,
+}
+
+impl<'a, T> ParallelEnqueuer<'a, T> {
+    /// Enqueue an element to be processed, by this or, if stolen, by another thread.
+    pub fn enqueue(&self, item: T) {
+        self.state.pending.fetch_add(1, Ordering::SeqCst);
+        self.worker.push(item);
+        self.state.notify(false);
+    }
+}
+
+impl<'a, T> Debug for ParallelEnqueuer<'a, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ParallelEnqueuer").finish_non_exhaustive()
+    }
+}
+
+/// Take an element from the local deque, else from the injector, else steal from other threads.
+fn find_item<T>(local: &Worker<T>, injector: &Injector<T>, stealers: &[Stealer<T>]) -> Option<T> {
+    local.pop().or_else(|| {
+        iter::repeat_with(|| {
+            injector
+                .steal_batch_and_pop(local)
+                .or_else(|| stealers.iter().map(Stealer::steal).collect::<Steal<_>>())
+        })
+        .find(|steal| !steal.is_retry())
+        .and_then(Steal::success)
+    })
+}
+
+/// State shared by the threads of [run_parallel]: the number of pending elements, i.e. enqueued,
+/// but not yet processed ones, and the means for idle threads to sleep.
+#[derive(Default)]
+struct State {
+    pending: AtomicUsize,
+
+    /// Incremented on each notification, such that threads do not sleep if they have missed one.
+    epoch: AtomicUsize,
+
+    sleepers: AtomicUsize,
+    lock: Mutex<()>,
+    condvar: Condvar,
+}
+
+impl State {
+    /// Sleep until notified, unless there has been a notification since the given epoch.
+    fn sleep(&self, epoch: usize) {
+        let guard = self.lock.lock().unwrap_or_else(PoisonError::into_inner);
+        self.sleepers.fetch_add(1, Ordering::SeqCst);
+        // Notifiers increment the epoch before checking for sleepers, hence either the epoch has
+        // changed or they see this sleeper and notify once the lock is released by waiting.
+        if self.epoch.load(Ordering::SeqCst) == epoch {
+            let _guard = self
+                .condvar
+                .wait(guard)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+        self.sleepers.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    /// Notify one or all sleeping threads.
+    fn notify(&self, all: bool) {
+        self.epoch.fetch_add(1, Ordering::SeqCst);
+        if self.sleepers.load(Ordering::SeqCst) > 0 {
+            let _guard = self.lock.lock().unwrap_or_else(PoisonError::into_inner);
+            if all {
+                self.condvar.notify_all();
+            } else {
+                self.condvar.notify_one();
+            }
+        }
+    }
+}
+
+/// Decrements the pending count when dropped, notifying all sleeping threads once it is zero.
+struct Done<'a>(&'a State);
+
+impl<'a> Drop for Done<'a> {
+    fn drop(&mut self) {
+        if self.0.pending.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.0.notify(true);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{sync::atomic::AtomicU64, time::Duration};
+
+    #[test]
+    fn test() {
+        let count = AtomicU64::new(0);
+
+        // Complete binary tree of depth 16.
+        run_parallel([(0, 1_u64)], 4, |(depth, n), enqueuer| {
+            count.fetch_add(1, Ordering::Relaxed);
+            if depth < 16 {
+                enqueuer.enqueue((depth + 1, 2 * n));
+                enqueuer.enqueue((depth + 1, 2 * n + 1));
+            }
+        });
+
+        assert_eq!(count.into_inner(), (1 << 17) - 1);
+    }
+
+    #[test]
+    fn test_sleep() {
+        // While a single element is processed, the idle threads sleep instead of spinning.
+        run_parallel([()], 4, |_, enqueuer| {
+            let sleepers = &enqueuer.state.sleepers;
+            for _ in 0..1000 {
+                if sleepers.load(Ordering::SeqCst) == 3 {
+                    break;
+                }
+                thread::sleep(Duration::from_millis(10));
+            }
+            assert_eq!(sleepers.load(Ordering::SeqCst), 3);
+        });
+    }
+
+    #[test]
+    fn test_no_seeds() {
+        run_parallel(Vec::<u32>::new(), 2, |_, _| unreachable!());
+    }
+}
@@ -0,0 +1,368 @@
+use crate::{queue_iter_with, Frontier, InspectFrontier, QueueIter};
+use std::{
+    cmp::Ordering,
+    fmt::{self, Debug, Formatter},
+    slice,
+};
+
+/// Create an `Iterator` allowing for enqueuing elements which are yielded in priority order, i.e.
+/// greatest first.
+///
+/// # Examples
+///
+/// ```
+/// use enqueue::priority_queue_iter;
+///
+/// let i = std::iter::once(666);
+/// let mut i = priority_queue_iter(i);
+///
+/// i.enqueue(1);
+/// i.enqueue(42);
+/// i.enqueue(7);
+/// assert_eq!(i.next(), Some(666));
+/// assert_eq!(i.next(), Some(42));
+/// assert_eq!(i.next(), Some(7));
+/// assert_eq!(i.next(), Some(1));
+/// assert_eq!(i.next(), None);
+/// ```
+pub fn priority_queue_iter<I>(initial: I) -> PriorityQueueIter<I::IntoIter>
+where
+    I: IntoIterator,
+    I::Item: Ord,
+{
+    priority_queue_iter_by(initial, NaturalOrder)
+}
+
+/// Create an `Iterator` allowing for enqueuing elements which are yielded in priority order of the
+/// keys extracted by the given function, i.e. greatest key first.
+///
+/// # Examples
+///
+/// ```
+/// use enqueue::priority_queue_iter_by_key;
+///
+/// let i = std::iter::empty();
+/// let mut i = priority_queue_iter_by_key(i, |s: &&str| s.len());
+///
+/// i.enqueue("a");
+/// i.enqueue("abc");
+/// i.enqueue("ab");
+/// assert_eq!(i.next(), Some("abc"));
+/// assert_eq!(i.next(), Some("ab"));
+/// assert_eq!(i.next(), Some("a"));
+/// assert_eq!(i.next(), None);
+/// ```
+pub fn priority_queue_iter_by_key<I, F, K>(
+    initial: I,
+    f: F,
+) -> PriorityQueueIter<I::IntoIter, ByKey<F>>
+where
+    I: IntoIterator,
+    F: FnMut(&I::Item) -> K,
+    K: Ord,
+{
+    priority_queue_iter_by(initial, ByKey(f))
+}
+
+/// Create an `Iterator` allowing for enqueuing elements which are yielded in priority order
+/// defined by the given comparator, i.e. greatest first.
+///
+/// # Examples
+///
+/// ```
+/// use enqueue::priority_queue_iter_by;
+///
+/// let i = std::iter::empty();
+/// let mut i = priority_queue_iter_by(i, |a: &i32, b: &i32| b.cmp(a));
+///
+/// i.enqueue(42);
+/// i.enqueue(1);
+/// assert_eq!(i.next(), Some(1));
+/// assert_eq!(i.next(), Some(42));
+/// assert_eq!(i.next(), None);
+/// ```
+pub fn priority_queue_iter_by<I, C>(initial: I, compare: C) -> PriorityQueueIter<I::IntoIter, C>
+where
+    I: IntoIterator,
+    C: Compare<I::Item>,
+{
+    queue_iter_with(initial, Priority::new(compare))
+}
+
+/// Comparison of elements of a [Priority] frontier. Implemented for closures of type
+/// `FnMut(&T, &T) -> Ordering`.
+pub trait Compare<T> {
+    /// Compare the given elements.
+    fn compare(&mut self, a: &T, b: &T) -> Ordering;
+}
+
+impl<T, F> Compare<T> for F
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    fn compare(&mut self, a: &T, b: &T) -> Ordering {
+        self(a, b)
+    }
+}
+
+/// [Compare] elements by their `Ord` implementation.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NaturalOrder;
+
+impl<T> Compare<T> for NaturalOrder
+where
+    T: Ord,
+{
+    fn compare(&mut self, a: &T, b: &T) -> Ordering {
+        a.cmp(b)
+    }
+}
+
+/// [Compare] elements by the keys extracted by the wrapped function.
+#[derive(Debug, Clone, Copy)]
+pub struct ByKey<F>(pub F);
+
+impl<T, F, K> Compare<T> for ByKey<F>
+where
+    F: FnMut(&T) -> K,
+    K: Ord,
+{
+    fn compare(&mut self, a: &T, b: &T) -> Ordering {
+        (self.0)(a).cmp(&(self.0)(b))
+    }
+}
+
+/// An `Iterator` allowing for enqueuing elements which are yielded in priority order, i.e.
+/// greatest first, after the initial elements. Enqueued elements of equal priority are yielded in
+/// FIFO order. Elements can be added anytime, even after calling `next` has returned `None`, i.e.
+/// this `Iterator` can return `Some` after `None`.
+pub type PriorityQueueIter<I, C = NaturalOrder> = QueueIter<I, Priority<<I as Iterator>::Item, C>>;
+
+/// [Frontier] yielding elements in priority order defined by a [Compare], i.e. greatest first,
+/// e.g. for best-first searches. Elements of equal priority are yielded in FIFO order.
+#[derive(Clone)]
+pub struct Priority<T, C = NaturalOrder> {
+    heap: Vec<Entry<T>>,
+    seq: u64,
+    compare: C,
+}
+
+impl<T, C> Priority<T, C>
+where
+    C: Compare<T>,
+{
+    /// Create a [Priority] frontier with the given [Compare].
+    pub fn new(compare: C) -> Self {
+        Self {
+            heap: Vec::default(),
+            seq: 0,
+            compare,
+        }
+    }
+
+    fn heapify(&mut self) {
+        for n in (0..self.heap.len() / 2).rev() {
+            self.sift_down(n);
+        }
+    }
+
+    fn sift_up(&mut self, mut n: usize) {
+        while n > 0 {
+            let parent = (n - 1) / 2;
+            if !self.before(n, parent) {
+                break;
+            }
+            self.heap.swap(n, parent);
+            n = parent;
+        }
+    }
+
+    fn sift_down(&mut self, mut n: usize) {
+        loop {
+            let mut first = n;
+            for child in [2 * n + 1, 2 * n + 2] {
+                if child < self.heap.len() && self.before(child, first) {
+                    first = child;
+                }
+            }
+            if first == n {
+                break;
+            }
+            self.heap.swap(n, first);
+            n = first;
+        }
+    }
+
+    /// Whether the entry at `a` is to be yielded before the one at `b`: greater priority first,
+    /// earlier pushed first for equal priority.
+    fn before(&mut self, a: usize, b: usize) -> bool {
+        let (a, b) = (&self.heap[a], &self.heap[b]);
+        self.compare
+            .compare(&a.item, &b.item)
+            .then_with(|| b.seq.cmp(&a.seq))
+            .is_gt()
+    }
+}
+
+impl<T, C> Debug for Priority<T, C>
+where
+    T: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let items = self
+            .heap
+            .iter()
+            .map(|entry| &entry.item)
+            .collect::<Vec<_>>();
+        f.debug_struct("Priority")
+            .field("items", &items)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T, C> Default for Priority<T, C>
+where
+    C: Compare<T> + Default,
+{
+    fn default() -> Self {
+        Self::new(C::default())
+    }
+}
+
+impl<T, C> Frontier<T> for Priority<T, C>
+where
+    C: Compare<T>,
+{
+    fn push(&mut self, item: T) {
+        let seq = self.seq;
+        self.seq += 1;
+        self.heap.push(Entry { seq, item });
+        self.sift_up(self.heap.len() - 1);
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        if self.heap.is_empty() {
+            return None;
+        }
+        let entry = self.heap.swap_remove(0);
+        self.sift_down(0);
+        Some(entry.item)
+    }
+
+    /// Takes linear time.
+    fn pop_oldest(&mut self) -> Option<T> {
+        let (n, _) = self
+            .heap
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, entry)| entry.seq)?;
+        let entry = self.heap.swap_remove(n);
+        if n < self.heap.len() {
+            self.sift_down(n);
+            self.sift_up(n);
+        }
+        Some(entry.item)
+    }
+
+    fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    fn clear(&mut self) {
+        self.heap.clear()
+    }
+}
+
+/// Elements are not mutable in place, because that could violate the heap order.
+impl<T, C> InspectFrontier<T> for Priority<T, C>
+where
+    C: Compare<T>,
+{
+    type Iter<'a>
+        = PriorityIter<'a, T>
+    where
+        T: 'a,
+        C: 'a;
+
+    /// Iterate over references to the elements in arbitrary order.
+    fn iter(&self) -> Self::Iter<'_> {
+        PriorityIter(self.heap.iter())
+    }
+
+    fn retain<P>(&mut self, mut f: P)
+    where
+        P: FnMut(&T) -> bool,
+    {
+        self.heap.retain(|entry| f(&entry.item));
+        self.heapify();
+    }
+}
+
+/// `Iterator` over references to the elements of a [Priority] frontier in arbitrary order.
+#[derive(Debug, Clone)]
+pub struct PriorityIter<'a, T>(slice::Iter<'a, Entry<T>>);
+
+impl<'a, T> Iterator for PriorityIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|entry| &entry.item)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Entry<T> {
+    seq: u64,
+    item: T,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cmp::Reverse;
+
+    #[test]
+    fn test() {
+        let mut items = priority_queue_iter_by_key(None, |&(_, n): &(char, u32)| Reverse(n));
+        for (c, n) in [('a', 3), ('b', 1), ('c', 2), ('d', 1), ('e', 3), ('f', 1)] {
+            items.enqueue((c, n));
+        }
+
+        let items = items.map(|(c, _)| c).collect::<String>();
+        assert_eq!(items, "bdfcae");
+    }
+
+    #[test]
+    fn test_best_first() {
+        let mut numbers = priority_queue_iter([1]);
+        let mut visited = vec![];
+
+        while let Some(n) = numbers.next() {
+            visited.push(n);
+            if n < 4 {
+                numbers.enqueue(2 * n);
+                numbers.enqueue(2 * n + 1);
+            }
+        }
+
+        assert_eq!(visited, [1, 3, 7, 6, 2, 5, 4]);
+
+        numbers.enqueue(42);
+        assert_eq!(numbers.next(), Some(42));
+    }
+
+    #[test]
+    fn test_pop_oldest() {
+        let mut priority = Priority::<_>::default();
+        for n in [5, 9, 1, 7, 3, 8, 2] {
+            priority.push(n);
+        }
+        assert_eq!(priority.pop_oldest(), Some(5));
+        assert_eq!(priority.pop_oldest(), Some(9));
+        assert_eq!(priority.pop(), Some(8));
+        assert_eq!(priority.pop_oldest(), Some(1));
+
+        let numbers = std::iter::from_fn(|| priority.pop()).collect::<Vec<_>>();
+        assert_eq!(numbers, [7, 3, 2]);
+    }
+}
@@ -0,0 +1,346 @@
+use crate::{ByteSize, Frontier};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    collections::VecDeque,
+    fmt::{self, Debug, Formatter},
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+use tempfile::TempDir;
+
+/// [Frontier] yielding elements in FIFO order like [Fifo](crate::Fifo), but spilling elements to
+/// disk when they exceed a memory threshold, e.g. for large crawls. The head and the tail of the
+/// queue are kept in memory, each holding at most `segment_len` elements and optionally at most a
+/// total [ByteSize], see [Spill::with_segment_bytes]; once the tail is full, it is serialized to a
+/// segment file in a temporary directory, from where it is read back when its turn comes. Hence
+/// at most about twice the threshold is held in memory. The temporary directory and all remaining
+/// segment files are removed on drop.
+///
+/// # Panics
+///
+/// Pushing and popping elements panics if spilling to or reading back from disk fails, e.g.
+/// because the disk is full; use [Spill::try_push] and [Spill::try_pop] to handle such errors.
+///
+/// # Examples
+///
+/// ```
+/// use enqueue::{IteratorExt, Spill};
+///
+/// let spill = Spill::new(2).unwrap();
+/// let mut i = std::iter::empty().queue_iter_with(spill);
+///
+/// i.extend(0..10);
+/// assert_eq!(i.collect::<Vec<_>>(), (0..10).collect::<Vec<_>>());
+/// ```
+pub struct Spill<T> {
+    head: VecDeque<T>,
+    segments: VecDeque<Segment>,
+    tail: VecDeque<T>,
+    segment_len: usize,
+    segment_bytes: usize,
+    size: fn(&T) -> usize,
+    head_bytes: usize,
+    tail_bytes: usize,
+    spilled: usize,
+    next_segment: u64,
+    dir: TempDir,
+}
+
+impl<T> Spill<T> {
+    /// Create a [Spill] frontier holding at most `segment_len` elements in its head and tail
+    /// each, using a new temporary directory in the default location for segment files.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `segment_len` is zero.
+    pub fn new(segment_len: usize) -> io::Result<Self> {
+        Self::with_dir(segment_len, tempfile::tempdir()?)
+    }
+
+    /// Create a [Spill] frontier like [Spill::new], but using a new temporary directory within
+    /// the given one for segment files.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `segment_len` is zero.
+    pub fn new_in<P>(segment_len: usize, dir: P) -> io::Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        Self::with_dir(segment_len, tempfile::tempdir_in(dir)?)
+    }
+
+    fn with_dir(segment_len: usize, dir: TempDir) -> io::Result<Self> {
+        assert!(segment_len > 0, "segment length must not be zero");
+        Ok(Self {
+            head: VecDeque::default(),
+            segments: VecDeque::default(),
+            tail: VecDeque::default(),
+            segment_len,
+            segment_bytes: usize::MAX,
+            size: |_| 0,
+            head_bytes: 0,
+            tail_bytes: 0,
+            spilled: 0,
+            next_segment: 0,
+            dir,
+        })
+    }
+
+    /// Additionally limit the head and the tail to the given total [ByteSize] each, i.e. spill the
+    /// tail once either limit is reached. A single element exceeding the limit is held anyway.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `segment_bytes` is zero.
+    pub fn with_segment_bytes(mut self, segment_bytes: usize) -> Self
+    where
+        T: ByteSize,
+    {
+        assert!(segment_bytes > 0, "segment bytes must not be zero");
+        self.segment_bytes = segment_bytes;
+        self.size = T::byte_size;
+        self.head_bytes = self.head.iter().map(T::byte_size).sum();
+        self.tail_bytes = self.tail.iter().map(T::byte_size).sum();
+        self
+    }
+
+    /// The number of elements currently spilled to disk.
+    pub fn spilled(&self) -> usize {
+        self.spilled
+    }
+
+    /// The temporary directory holding the segment files.
+    pub fn dir(&self) -> &Path {
+        self.dir.path()
+    }
+
+    /// Whether the head or the tail with the given length and total size is full for the given
+    /// element.
+    fn is_full(&self, len: usize, bytes: usize, item: &T) -> bool {
+        len >= self.segment_len
+            || len > 0 && bytes.saturating_add((self.size)(item)) > self.segment_bytes
+    }
+}
+
+impl<T> Spill<T>
+where
+    T: Serialize + DeserializeOwned,
+{
+    /// Add an element, spilling the tail to disk if it is full.
+    pub fn try_push(&mut self, item: T) -> io::Result<()> {
+        let size = (self.size)(&item);
+        // Without spilled elements, the tail directly follows the head, hence keep it in memory.
+        if self.segments.is_empty()
+            && self.tail.is_empty()
+            && !self.is_full(self.head.len(), self.head_bytes, &item)
+        {
+            self.head.push_back(item);
+            self.head_bytes += size;
+            return Ok(());
+        }
+
+        if self.is_full(self.tail.len(), self.tail_bytes, &item) {
+            self.spill()?;
+        }
+        self.tail.push_back(item);
+        self.tail_bytes += size;
+        Ok(())
+    }
+
+    /// Remove the element to be yielded next, if any, reading back spilled elements from disk if
+    /// the head is empty.
+    pub fn try_pop(&mut self) -> io::Result<Option<T>> {
+        if self.head.is_empty() {
+            self.unspill()?;
+        }
+        let item = match self.head.pop_front() {
+            Some(item) => {
+                self.head_bytes -= (self.size)(&item);
+                item
+            }
+            None => match self.tail.pop_front() {
+                Some(item) => {
+                    self.tail_bytes -= (self.size)(&item);
+                    item
+                }
+                None => return Ok(None),
+            },
+        };
+        Ok(Some(item))
+    }
+
+    /// Serialize the tail to a new segment file.
+    fn spill(&mut self) -> io::Result<()> {
+        let path = self
+            .dir
+            .path()
+            .join(format!("segment-{}.bin", self.next_segment));
+        let mut file = BufWriter::new(File::create(&path)?);
+        bincode::serialize_into(&mut file, &self.tail).map_err(|error| into_io_error(*error))?;
+        file.flush()?;
+
+        self.next_segment += 1;
+        self.spilled += self.tail.len();
+        self.segments.push_back(Segment {
+            path,
+            len: self.tail.len(),
+        });
+        self.tail.clear();
+        self.tail_bytes = 0;
+        Ok(())
+    }
+
+    /// Read back the first segment file into the head and remove it.
+    fn unspill(&mut self) -> io::Result<()> {
+        if let Some(segment) = self.segments.front() {
+            let file = BufReader::new(File::open(&segment.path)?);
+            self.head = bincode::deserialize_from(file).map_err(|error| into_io_error(*error))?;
+            self.head_bytes = self.head.iter().map(self.size).sum();
+            fs::remove_file(&segment.path)?;
+
+            self.spilled -= segment.len;
+            self.segments.pop_front();
+        }
+        Ok(())
+    }
+}
+
+impl<T> Debug for Spill<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Spill")
+            .field("len", &(self.head.len() + self.spilled + self.tail.len()))
+            .field("spilled", &self.spilled)
+            .field("segment_len", &self.segment_len)
+            .field("segment_bytes", &self.segment_bytes)
+            .field("dir", &self.dir.path())
+            .finish()
+    }
+}
+
+impl<T> Frontier<T> for Spill<T>
+where
+    T: Serialize + DeserializeOwned,
+{
+    fn push(&mut self, item: T) {
+        if let Err(error) = self.try_push(item) {
+            panic!("cannot spill elements to {}: {error}", self.dir().display());
+        }
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        match self.try_pop() {
+            Ok(item) => item,
+            Err(error) => panic!(
+                "cannot read back elements from {}: {error}",
+                self.dir().display()
+            ),
+        }
+    }
+
+    fn pop_oldest(&mut self) -> Option<T> {
+        self.pop()
+    }
+
+    fn len(&self) -> usize {
+        self.head.len() + self.spilled + self.tail.len()
+    }
+
+    /// Remove all elements without reading back the segment files.
+    fn clear(&mut self) {
+        self.head.clear();
+        self.tail.clear();
+        self.head_bytes = 0;
+        self.tail_bytes = 0;
+        for segment in self.segments.drain(..) {
+            // A remaining file is removed with the temporary directory on drop at the latest.
+            let _ = fs::remove_file(segment.path);
+        }
+        self.spilled = 0;
+    }
+}
+
+#[derive(Debug)]
+struct Segment {
+    path: PathBuf,
+    len: usize,
+}
+
+fn into_io_error(error: bincode::ErrorKind) -> io::Error {
+    match error {
+        bincode::ErrorKind::Io(error) => error,
+        error => io::Error::new(io::ErrorKind::InvalidData, error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::IteratorExt;
+
+    #[test]
+    fn test() {
+        let spill = Spill::new(3).unwrap();
+        let dir = spill.dir().to_path_buf();
+        let mut words = ["a"].map(String::from).queue_iter_with(spill);
+
+        let mut expected = vec![String::from("a")];
+        for n in 0..20 {
+            words.enqueue(n.to_string());
+            expected.push(n.to_string());
+        }
+        assert_eq!(words.pending_len(), 20);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 5);
+
+        // Interleave enqueuing and yielding.
+        let mut yielded = vec![];
+        for n in 20..30 {
+            yielded.extend(words.next());
+            words.enqueue(n.to_string());
+            expected.push(n.to_string());
+        }
+        yielded.extend(words.by_ref());
+        assert_eq!(yielded, expected);
+
+        words.enqueue(String::from("b"));
+        assert_eq!(words.next().as_deref(), Some("b"));
+
+        // Clearing removes the segment files without reading them back.
+        words.extend((0..10).map(|n| n.to_string()));
+        words.clear_pending();
+        assert_eq!(words.pending_len(), 0);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+
+        drop(words);
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn test_segment_bytes() {
+        let mut spill = Spill::new(100).unwrap().with_segment_bytes(10);
+        for word in ["abcd", "efgh", "ijkl", "mnop", "q", "rstuvwxyzab", "cd"] {
+            spill.try_push(word.to_string()).unwrap();
+        }
+        // The head holds two words; the next three words and the long one on its own are spilled.
+        assert_eq!(spill.spilled(), 4);
+        assert_eq!(fs::read_dir(spill.dir()).unwrap().count(), 2);
+
+        let words = std::iter::from_fn(|| spill.try_pop().unwrap()).collect::<String>();
+        assert_eq!(words, "abcdefghijklmnopqrstuvwxyzabcd");
+    }
+
+    #[test]
+    fn test_error() {
+        let mut spill = Spill::new(1).unwrap();
+        spill.try_push(1).unwrap();
+        spill.try_push(2).unwrap();
+        fs::remove_dir(spill.dir()).unwrap();
+
+        // Spilling fails without losing the elements held in memory.
+        assert!(spill.try_push(3).is_err());
+        assert_eq!(spill.try_pop().unwrap(), Some(1));
+        assert_eq!(spill.try_pop().unwrap(), Some(2));
+        assert_eq!(spill.try_pop().unwrap(), None);
+    }
+}
@@ -0,0 +1,60 @@
+use crate::{queue_iter_with, Lifo, QueueIter};
+
+/// Create an `Iterator` allowing for enqueuing elements which are yielded in LIFO order, i.e. most
+/// recently enqueued first, e.g. to drive a depth-first traversal.
+///
+/// # Examples
+///
+/// ```
+/// use enqueue::stack_iter;
+///
+/// let i = std::iter::once(666);
+/// let mut i = stack_iter(i);
+///
+/// i.enqueue(1);
+/// i.enqueue(42);
+/// assert_eq!(i.next(), Some(666));
+/// assert_eq!(i.next(), Some(42));
+/// assert_eq!(i.next(), Some(1));
+/// assert_eq!(i.next(), None);
+///
+/// i.enqueue(42);
+/// assert_eq!(i.next(), Some(42));
+/// ```
+pub fn stack_iter<I>(initial: I) -> StackIter<I::IntoIter>
+where
+    I: IntoIterator,
+{
+    queue_iter_with(initial, Lifo::default())
+}
+
+/// An `Iterator` allowing for enqueuing elements which are yielded in LIFO order after the initial
+/// elements. Elements can be added anytime, even after calling `next` has returned `None`, i.e.
+/// this `Iterator` can return `Some` after `None`.
+pub type StackIter<I> = QueueIter<I, Lifo<<I as Iterator>::Item>>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test() {
+        // Binary tree with nodes 1..=7, node n having children 2n and 2n + 1.
+        let mut nodes = stack_iter([1]);
+        let mut visited = vec![];
+
+        while let Some(n) = nodes.next() {
+            visited.push(n);
+            if n < 4 {
+                nodes.enqueue(2 * n + 1);
+                nodes.enqueue(2 * n);
+            }
+        }
+
+        // Pre-order depth-first.
+        assert_eq!(visited, [1, 2, 4, 5, 3, 6, 7]);
+
+        nodes.enqueue(42);
+        assert_eq!(nodes.next(), Some(42));
+    }
+}
@@ -0,0 +1,453 @@
+use crate::Closed;
+use futures_core::Stream;
+use std::{
+    collections::VecDeque,
+    error::Error,
+    fmt::{self, Debug, Display, Formatter},
+    mem,
+    pin::Pin,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    task::{Context, Poll, Waker},
+};
+
+/// Create a `Stream` allowing for enqueuing elements to its end, from the consumer via
+/// [QueueStream::enqueue] or from anywhere via cloneable [StreamEnqueuer] handles.
+///
+/// Initial elements are yielded first; while the initial `Stream` is pending, enqueued elements
+/// are yielded. Once both are exhausted, the `QueueStream` is pending until a further element gets
+/// enqueued, and it terminates once it has been closed or no handles exist. Alternatively it can be
+/// configured to yield `None` when idle, see [QueueStream::with_none_when_idle].
+///
+/// # Examples
+///
+/// ```
+/// use enqueue::queue_stream;
+/// use futures::{executor::block_on, stream, StreamExt};
+///
+/// let s = stream::iter([666]);
+/// let s = queue_stream(s);
+///
+/// let enqueuer = s.enqueuer();
+/// block_on(enqueuer.enqueue(42)).unwrap();
+/// drop(enqueuer);
+///
+/// let items = block_on(s.collect::<Vec<_>>());
+/// assert_eq!(items, [666, 42]);
+/// ```
+pub fn queue_stream<S>(initial: S) -> QueueStream<S>
+where
+    S: Stream,
+{
+    QueueStream {
+        initial: Some(Box::pin(initial)),
+        shared: Arc::new(Shared {
+            state: Mutex::new(State {
+                queue: VecDeque::default(),
+                capacity: None,
+                enqueuers: 0,
+                closed: false,
+                waker: None,
+                producers: Vec::default(),
+            }),
+        }),
+        none_when_idle: false,
+    }
+}
+
+/// A `Stream` allowing for enqueuing elements to its end, see [queue_stream].
+pub struct QueueStream<S>
+where
+    S: Stream,
+{
+    initial: Option<Pin<Box<S>>>,
+    shared: Arc<Shared<S::Item>>,
+    none_when_idle: bool,
+}
+
+impl<S> QueueStream<S>
+where
+    S: Stream,
+{
+    /// Yield `None` instead of being pending when there are no more initial and enqueued elements,
+    /// like [QueueIter](crate::QueueIter), i.e. this `Stream` can yield `Some` after `None`.
+    pub fn with_none_when_idle(mut self) -> Self {
+        self.none_when_idle = true;
+        self
+    }
+
+    /// Limit the number of enqueued elements to the given capacity, applying backpressure to
+    /// [StreamEnqueuer] handles: [StreamEnqueuer::enqueue] waits until this `Stream` has yielded
+    /// an element to make room and [StreamEnqueuer::try_enqueue] fails. Initial elements and
+    /// elements enqueued via [QueueStream::enqueue] are not limited, but the latter count towards
+    /// the capacity.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use enqueue::{queue_stream, TryEnqueueError};
+    /// use futures::{executor::block_on, stream, StreamExt};
+    ///
+    /// let mut s = queue_stream(stream::empty()).with_capacity_limit(1);
+    /// let enqueuer = s.enqueuer();
+    ///
+    /// assert_eq!(enqueuer.try_enqueue(1), Ok(()));
+    /// assert_eq!(enqueuer.try_enqueue(2), Err(TryEnqueueError::Full(2)));
+    ///
+    /// assert_eq!(block_on(s.next()), Some(1));
+    /// assert_eq!(enqueuer.try_enqueue(2), Ok(()));
+    /// ```
+    pub fn with_capacity_limit(self, capacity: usize) -> Self {
+        assert!(capacity > 0, "capacity must not be zero");
+        self.shared.lock().capacity = Some(capacity);
+        self
+    }
+
+    /// Enqueue an element to the end of this `Stream`. Contrary to [StreamEnqueuer::enqueue] this
+    /// also succeeds if the queue has been closed or is full.
+    pub fn enqueue(&mut self, item: S::Item) {
+        self.shared.lock().queue.push_back(item);
+    }
+
+    /// Close the queue: [StreamEnqueuer] handles can no longer enqueue elements and this `Stream`
+    /// terminates once there are no more elements.
+    pub fn close(&self) {
+        self.shared.close();
+    }
+
+    /// Create a [StreamEnqueuer] handle.
+    pub fn enqueuer(&self) -> StreamEnqueuer<S::Item> {
+        self.shared.lock().enqueuers += 1;
+        StreamEnqueuer {
+            shared: self.shared.clone(),
+        }
+    }
+
+    /// Poll for the next element. If idle, i.e. there are no initial and no enqueued elements, and
+    /// `none_when_idle` is `true`, `None` is returned, but nevertheless the waker is registered to
+    /// get notified about further enqueued elements. Used by `Stream::poll_next` with the
+    /// configured flag and by [run_concurrent](crate::run_concurrent) to detect idleness.
+    pub(crate) fn poll_next_item(
+        &mut self,
+        cx: &mut Context<'_>,
+        none_when_idle: bool,
+    ) -> Poll<Option<S::Item>> {
+        if let Some(initial) = &mut self.initial {
+            match initial.as_mut().poll_next(cx) {
+                Poll::Ready(Some(item)) => return Poll::Ready(Some(item)),
+                Poll::Ready(None) => self.initial = None,
+                Poll::Pending => {}
+            }
+        }
+
+        // Clone the waker before locking, see Shared::lock; as it is declared before the lock
+        // guard, it is also dropped after it.
+        let waker = cx.waker().clone();
+        let mut state = self.shared.lock();
+        if let Some(item) = state.queue.pop_front() {
+            let producers = mem::take(&mut state.producers);
+            drop(state);
+            producers.into_iter().for_each(Waker::wake);
+            return Poll::Ready(Some(item));
+        }
+        if self.initial.is_none() && (state.closed || state.enqueuers == 0) {
+            return Poll::Ready(None);
+        }
+        let old_waker = state.waker.replace(waker);
+        drop(state);
+        drop(old_waker);
+        if self.initial.is_none() && none_when_idle {
+            Poll::Ready(None)
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+impl<S> Stream for QueueStream<S>
+where
+    S: Stream,
+{
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let none_when_idle = this.none_when_idle;
+        this.poll_next_item(cx, none_when_idle)
+    }
+}
+
+/// Dropping a [QueueStream] closes the queue, such that [StreamEnqueuer] handles do not wait for
+/// room forever.
+impl<S> Drop for QueueStream<S>
+where
+    S: Stream,
+{
+    fn drop(&mut self) {
+        self.shared.close();
+    }
+}
+
+impl<S> Debug for QueueStream<S>
+where
+    S: Stream,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("QueueStream")
+            .field("none_when_idle", &self.none_when_idle)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Cloneable, thread-safe handle for enqueuing elements to the end of a [QueueStream].
+pub struct StreamEnqueuer<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> StreamEnqueuer<T> {
+    /// Enqueue an element to the end of the [QueueStream], waking it up if pending, unless the
+    /// queue has been closed, in which case the element is returned in a [Closed] error. If the
+    /// queue is full, see [QueueStream::with_capacity_limit], wait until there is room.
+    pub async fn enqueue(&self, item: T) -> Result<(), Closed<T>> {
+        let mut item = Some(item);
+        std::future::poll_fn(|cx| {
+            // Clone the waker before locking, see Shared::lock; as it is declared before the lock
+            // guard, it is also dropped after it.
+            let waker = cx.waker().clone();
+            let mut state = self.shared.lock();
+            if state.closed {
+                let item = item.take().expect("enqueue polled after completion");
+                return Poll::Ready(Err(Closed(item)));
+            }
+            if state.is_full() {
+                if !state.producers.iter().any(|w| w.will_wake(&waker)) {
+                    state.producers.push(waker);
+                }
+                return Poll::Pending;
+            }
+            let item = item.take().expect("enqueue polled after completion");
+            Shared::push_back(state, item);
+            Poll::Ready(Ok(()))
+        })
+        .await
+    }
+
+    /// Enqueue an element to the end of the [QueueStream] without waiting, waking it up if
+    /// pending, unless the queue has been closed or is full, see
+    /// [QueueStream::with_capacity_limit], in which case the element is returned in a
+    /// [TryEnqueueError].
+    pub fn try_enqueue(&self, item: T) -> Result<(), TryEnqueueError<T>> {
+        let state = self.shared.lock();
+        if state.closed {
+            return Err(TryEnqueueError::Closed(item));
+        }
+        if state.is_full() {
+            return Err(TryEnqueueError::Full(item));
+        }
+        Shared::push_back(state, item);
+        Ok(())
+    }
+
+    /// Close the queue, see [QueueStream::close].
+    pub fn close(&self) {
+        self.shared.close();
+    }
+}
+
+impl<T> Clone for StreamEnqueuer<T> {
+    fn clone(&self) -> Self {
+        self.shared.lock().enqueuers += 1;
+        Self {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T> Drop for StreamEnqueuer<T> {
+    fn drop(&mut self) {
+        let mut state = self.shared.lock();
+        state.enqueuers -= 1;
+        let waker = (state.enqueuers == 0).then(|| state.waker.take()).flatten();
+        drop(state);
+        waker.into_iter().for_each(Waker::wake);
+    }
+}
+
+impl<T> Debug for StreamEnqueuer<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StreamEnqueuer").finish_non_exhaustive()
+    }
+}
+
+struct Shared<T> {
+    state: Mutex<State<T>>,
+}
+
+impl<T> Shared<T> {
+    fn lock(&self) -> MutexGuard<'_, State<T>> {
+        // The state is always consistent, because no user code is run while holding the lock.
+        // Neither is any waker code, i.e. wakers are cloned, woken and dropped outside of it, as
+        // they may call into the executor.
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Push the given element and wake up the consumer, releasing the given lock before.
+    fn push_back(mut state: MutexGuard<'_, State<T>>, item: T) {
+        state.queue.push_back(item);
+        let waker = state.waker.take();
+        drop(state);
+        waker.into_iter().for_each(Waker::wake);
+    }
+
+    fn close(&self) {
+        let mut state = self.lock();
+        state.closed = true;
+        let waker = state.waker.take();
+        let producers = mem::take(&mut state.producers);
+        drop(state);
+        waker.into_iter().chain(producers).for_each(Waker::wake);
+    }
+}
+
+struct State<T> {
+    queue: VecDeque<T>,
+    capacity: Option<usize>,
+    enqueuers: usize,
+    closed: bool,
+    waker: Option<Waker>,
+    producers: Vec<Waker>,
+}
+
+impl<T> State<T> {
+    fn is_full(&self) -> bool {
+        self.capacity
+            .is_some_and(|capacity| self.queue.len() >= capacity)
+    }
+}
+
+/// Error returned by [StreamEnqueuer::try_enqueue], containing the rejected element.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TryEnqueueError<T> {
+    /// The queue is full, see [QueueStream::with_capacity_limit].
+    Full(T),
+
+    /// The queue has been closed.
+    Closed(T),
+}
+
+impl<T> TryEnqueueError<T> {
+    /// Return the rejected element.
+    pub fn into_inner(self) -> T {
+        match self {
+            Self::Full(item) | Self::Closed(item) => item,
+        }
+    }
+}
+
+impl<T> Display for TryEnqueueError<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Full(_) => write!(f, "queue full"),
+            Self::Closed(_) => write!(f, "queue closed"),
+        }
+    }
+}
+
+impl<T> Error for TryEnqueueError<T> where T: Debug {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::{executor::block_on, poll, stream, StreamExt};
+    use std::{pin::pin, thread};
+
+    #[test]
+    fn test() {
+        let mut numbers = queue_stream(stream::iter(0..10));
+        let enqueuer = numbers.enqueuer();
+
+        let producer = thread::spawn(move || {
+            for n in 0..100 {
+                block_on(enqueuer.enqueue(n)).unwrap();
+            }
+            enqueuer.close();
+        });
+
+        let sum = block_on(async {
+            let mut sum = 0;
+            while let Some(n) = numbers.next().await {
+                sum += n;
+                if n == 0 {
+                    numbers.enqueue(1000);
+                }
+            }
+            sum
+        });
+        producer.join().unwrap();
+
+        // 45 for 0..10, 4950 for the producer and 2 * 1000 for the zeros.
+        assert_eq!(sum, 45 + 4950 + 2 * 1000);
+    }
+
+    #[test]
+    fn test_none_when_idle() {
+        let mut numbers = queue_stream(stream::iter([1])).with_none_when_idle();
+        let _enqueuer = numbers.enqueuer();
+
+        block_on(async {
+            assert_eq!(numbers.next().await, Some(1));
+            assert_eq!(numbers.next().await, None);
+            numbers.enqueue(42);
+            assert_eq!(numbers.next().await, Some(42));
+            assert_eq!(numbers.next().await, None);
+        });
+    }
+
+    #[test]
+    fn test_capacity_limit() {
+        let mut numbers = queue_stream(stream::empty()).with_capacity_limit(1);
+        let enqueuer = numbers.enqueuer();
+
+        block_on(async {
+            enqueuer.enqueue(1).await.unwrap();
+            assert_eq!(enqueuer.try_enqueue(2), Err(TryEnqueueError::Full(2)));
+
+            let mut enqueue = pin!(enqueuer.enqueue(2));
+            assert!(poll!(&mut enqueue).is_pending());
+            assert_eq!(numbers.next().await, Some(1));
+            assert_eq!(poll!(&mut enqueue), Poll::Ready(Ok(())));
+
+            // The consumer is not limited.
+            numbers.enqueue(3);
+            assert_eq!(numbers.next().await, Some(2));
+            assert_eq!(numbers.next().await, Some(3));
+        });
+
+        // Waiting producers fail once the queue gets closed.
+        enqueuer.try_enqueue(4).unwrap();
+        let producer = thread::spawn(move || block_on(enqueuer.enqueue(5)));
+        drop(numbers);
+        assert_eq!(producer.join().unwrap(), Err(Closed(5)));
+    }
+
+    #[test]
+    fn test_backpressure() {
+        let numbers = queue_stream(stream::empty()).with_capacity_limit(2);
+        let enqueuer = numbers.enqueuer();
+        let shared = numbers.shared.clone();
+
+        let producer = thread::spawn(move || {
+            for n in 0..100 {
+                block_on(enqueuer.enqueue(n)).unwrap();
+                assert!(shared.lock().queue.len() <= 2);
+            }
+        });
+
+        let numbers = block_on(numbers.collect::<Vec<_>>());
+        producer.join().unwrap();
+        assert_eq!(numbers, (0..100).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,256 @@
+use std::{
+    collections::VecDeque,
+    error::Error,
+    fmt::{self, Debug, Display, Formatter},
+    sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError},
+};
+
+/// Create an `Iterator` allowing for enqueuing elements to its end together with a cloneable,
+/// thread-safe [SyncEnqueuer] handle. Once the initial elements and the enqueued ones are
+/// exhausted, `next` blocks until a further element gets enqueued, and only returns `None` once all
+/// handles have been dropped or the queue has been closed.
+///
+/// # Examples
+///
+/// ```
+/// use enqueue::sync_queue_iter;
+/// use std::thread;
+///
+/// let i = std::iter::once(666);
+/// let (i, enqueuer) = sync_queue_iter(i);
+///
+/// thread::spawn(move || {
+///     enqueuer.enqueue(42).unwrap();
+/// });
+///
+/// let items = i.collect::<Vec<_>>();
+/// assert_eq!(items, [666, 42]);
+/// ```
+pub fn sync_queue_iter<I>(initial: I) -> (SyncQueueIter<I::IntoIter>, SyncEnqueuer<I::Item>)
+where
+    I: IntoIterator,
+{
+    let shared = Arc::new(Shared {
+        state: Mutex::new(State {
+            queue: VecDeque::default(),
+            enqueuers: 1,
+            closed: false,
+        }),
+        available: Condvar::new(),
+    });
+    let queue_iter = SyncQueueIter {
+        initial: Some(initial.into_iter()),
+        shared: shared.clone(),
+    };
+    (queue_iter, SyncEnqueuer { shared })
+}
+
+/// An `Iterator` allowing for enqueuing elements to its end, also from other threads via
+/// [SyncEnqueuer] handles, see [sync_queue_iter].
+pub struct SyncQueueIter<I>
+where
+    I: Iterator,
+{
+    initial: Option<I>,
+    shared: Arc<Shared<I::Item>>,
+}
+
+impl<I> SyncQueueIter<I>
+where
+    I: Iterator,
+{
+    /// Enqueue an element to the end of this `Iterator`. Contrary to [SyncEnqueuer::enqueue] this
+    /// also succeeds if the queue has been closed.
+    pub fn enqueue(&mut self, item: I::Item) {
+        self.shared.lock().queue.push_back(item);
+    }
+
+    /// Close the queue: [SyncEnqueuer] handles can no longer enqueue elements and `next` returns
+    /// `None` instead of blocking once there are no more elements.
+    pub fn close(&self) {
+        self.shared.close();
+    }
+
+    /// Create a further [SyncEnqueuer] handle.
+    pub fn enqueuer(&self) -> SyncEnqueuer<I::Item> {
+        self.shared.lock().enqueuers += 1;
+        SyncEnqueuer {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<I> Iterator for SyncQueueIter<I>
+where
+    I: Iterator,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(item) = self.initial.as_mut().and_then(Iterator::next) {
+            return Some(item);
+        }
+        self.initial = None;
+
+        let mut state = self.shared.lock();
+        loop {
+            if let Some(item) = state.queue.pop_front() {
+                return Some(item);
+            }
+            if state.closed || state.enqueuers == 0 {
+                return None;
+            }
+            state = self
+                .shared
+                .available
+                .wait(state)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+    }
+}
+
+impl<I> Debug for SyncQueueIter<I>
+where
+    I: Iterator + Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SyncQueueIter")
+            .field("initial", &self.initial)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Cloneable, thread-safe handle for enqueuing elements to the end of a [SyncQueueIter].
+pub struct SyncEnqueuer<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> SyncEnqueuer<T> {
+    /// Enqueue an element to the end of the [SyncQueueIter], unless the queue has been closed, in
+    /// which case the element is returned in a [Closed] error.
+    pub fn enqueue(&self, item: T) -> Result<(), Closed<T>> {
+        let mut state = self.shared.lock();
+        if state.closed {
+            return Err(Closed(item));
+        }
+        state.queue.push_back(item);
+        drop(state);
+        self.shared.available.notify_one();
+        Ok(())
+    }
+
+    /// Close the queue, see [SyncQueueIter::close].
+    pub fn close(&self) {
+        self.shared.close();
+    }
+}
+
+impl<T> Clone for SyncEnqueuer<T> {
+    fn clone(&self) -> Self {
+        self.shared.lock().enqueuers += 1;
+        Self {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T> Drop for SyncEnqueuer<T> {
+    fn drop(&mut self) {
+        let mut state = self.shared.lock();
+        state.enqueuers -= 1;
+        if state.enqueuers == 0 {
+            drop(state);
+            self.shared.available.notify_all();
+        }
+    }
+}
+
+impl<T> Debug for SyncEnqueuer<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SyncEnqueuer").finish_non_exhaustive()
+    }
+}
+
+/// Error returned when enqueuing to a closed queue, containing the rejected element.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Closed<T>(pub T);
+
+impl<T> Display for Closed<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "queue closed")
+    }
+}
+
+impl<T> Error for Closed<T> where T: Debug {}
+
+struct Shared<T> {
+    state: Mutex<State<T>>,
+    available: Condvar,
+}
+
+impl<T> Shared<T> {
+    fn lock(&self) -> MutexGuard<'_, State<T>> {
+        // The state is always consistent, because no user code is run while holding the lock.
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn close(&self) {
+        self.lock().closed = true;
+        self.available.notify_all();
+    }
+}
+
+struct State<T> {
+    queue: VecDeque<T>,
+    enqueuers: usize,
+    closed: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test() {
+        let (mut numbers, enqueuer) = sync_queue_iter(0..10);
+
+        let producers = (0..4)
+            .map(|_| {
+                let enqueuer = enqueuer.clone();
+                thread::spawn(move || {
+                    for n in 0..100 {
+                        enqueuer.enqueue(n).unwrap();
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        drop(enqueuer);
+
+        let mut sum = 0;
+        while let Some(n) = numbers.next() {
+            sum += n;
+            if n == 0 {
+                numbers.enqueue(1000);
+            }
+        }
+        for producer in producers {
+            producer.join().unwrap();
+        }
+
+        // 45 for 0..10, 4 * 4950 for the producers and 5 * 1000 for the zeros.
+        assert_eq!(sum, 45 + 4 * 4950 + 5 * 1000);
+    }
+
+    #[test]
+    fn test_close() {
+        let (numbers, enqueuer) = sync_queue_iter(None);
+
+        let consumer = thread::spawn(move || numbers.collect::<Vec<_>>());
+        enqueuer.enqueue(42).unwrap();
+        enqueuer.close();
+        assert_eq!(enqueuer.enqueue(666), Err(Closed(666)));
+
+        assert_eq!(consumer.join().unwrap(), [42]);
+    }
+}